@@ -0,0 +1,102 @@
+//! Procedural macros for `flat_collections`, re-exported by it behind the `macros` feature.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use std::mem;
+use syn::{parse::{Parse, ParseStream}, parse_macro_input, punctuated::Punctuated, Expr, ExprLit, ExprUnary, Lit, Token, UnOp};
+
+/// A literal key, ordered the same way as the value it denotes.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum SortKey {
+    Bool(bool),
+    Int(i128),
+    Char(char),
+    Str(String),
+    ByteStr(Vec<u8>),
+}
+
+impl SortKey {
+    fn from_expr(expr: &Expr) -> syn::Result<Self> {
+        match expr {
+            Expr::Lit(ExprLit { lit, .. }) => Self::from_lit(lit, false),
+            Expr::Unary(ExprUnary { op: UnOp::Neg(_), expr, .. }) => match &**expr {
+                Expr::Lit(ExprLit { lit: lit @ Lit::Int(_), .. }) => Self::from_lit(lit, true),
+                _ => Err(syn::Error::new_spanned(expr, "expected an integer literal")),
+            },
+            _ => Err(syn::Error::new_spanned(expr, "keys must be literals")),
+        }
+    }
+
+    fn from_lit(lit: &Lit, negative: bool) -> syn::Result<Self> {
+        Ok(match lit {
+            Lit::Bool(b) => Self::Bool(b.value),
+            Lit::Int(i) => {
+                let value = i.base10_parse::<i128>()?;
+                Self::Int(if negative { -value } else { value })
+            }
+            Lit::Char(c) => Self::Char(c.value()),
+            Lit::Str(s) => Self::Str(s.value()),
+            Lit::ByteStr(s) => Self::ByteStr(s.value()),
+            _ => return Err(syn::Error::new_spanned(lit, "unsupported key literal")),
+        })
+    }
+}
+
+struct Entry {
+    key: Expr,
+    sort_key: SortKey,
+    value: Expr,
+}
+
+impl Parse for Entry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key = input.parse()?;
+        let sort_key = SortKey::from_expr(&key)?;
+        input.parse::<Token![=>]>()?;
+        let value = input.parse()?;
+        Ok(Self { key, sort_key, value })
+    }
+}
+
+struct Entries(Vec<Entry>);
+
+impl Parse for Entries {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let entries = Punctuated::<Entry, Token![,]>::parse_terminated(input)?;
+        let mut entries = entries.into_iter().collect::<Vec<_>>();
+        if let Some(first) = entries.first() {
+            let kind = mem::discriminant(&first.sort_key);
+            if let Some(other) = entries.iter().find(|e| mem::discriminant(&e.sort_key) != kind) {
+                return Err(syn::Error::new_spanned(&other.key, "all keys must be literals of the same kind"));
+            }
+        }
+
+        // like `FlatMap::from`, the last of several entries with the same key wins
+        entries.reverse();
+        entries.sort_by(|a, b| a.sort_key.cmp(&b.sort_key));
+        entries.dedup_by(|a, b| a.sort_key == b.sort_key);
+        Ok(Self(entries))
+    }
+}
+
+/// Builds a `FlatMapView<'static, K, V>` from `key => value` pairs, sorted at compile time.
+///
+/// Keys must be literals of one kind: integers, `char`s, `bool`s, strings or byte strings.
+/// Values can be any constant expressions. The result can initialize a `static` or `const`:
+///
+/// ```ignore
+/// static COLORS: FlatMapView<&str, u32> = frozen_flat_map! {
+///     "red" => 0xff0000,
+///     "green" => 0x00ff00,
+///     "blue" => 0x0000ff,
+/// };
+/// ```
+#[proc_macro]
+pub fn frozen_flat_map(input: TokenStream) -> TokenStream {
+    let Entries(entries) = parse_macro_input!(input as Entries);
+    let entries = entries.iter().map(|Entry { key, value, .. }| quote! { (#key, #value) });
+    quote! {
+        ::flat_collections::FlatMapView::from_sorted_unchecked(&[#(#entries),*])
+    }
+    .into()
+}
@@ -0,0 +1,75 @@
+//! Fixed-width integers packed into `u64` words, shared by the compressed frozen collections.
+
+/// Number of words needed for `len` values of `width` bits.
+pub(crate) fn words_for(len: usize, width: usize) -> usize {
+    (len * width).div_ceil(64)
+}
+
+/// Stores the low `width` bits of `value` as the `index`-th value of `words`, which must be
+/// zeroed there.
+pub(crate) fn pack(words: &mut [u64], index: usize, width: usize, value: u64) {
+    if width == 0 {
+        return;
+    }
+    let value = value & (u64::MAX >> (64 - width));
+    let bit = index * width;
+    words[bit / 64] |= value << (bit % 64);
+    if bit % 64 + width > 64 {
+        words[bit / 64 + 1] |= value >> (64 - bit % 64);
+    }
+}
+
+/// Returns the `index`-th `width`-bit value of `words`.
+pub(crate) fn unpack(words: &[u64], index: usize, width: usize) -> u64 {
+    if width == 0 {
+        return 0;
+    }
+    let bit = index * width;
+    let mut value = words[bit / 64] >> (bit % 64);
+    if bit % 64 + width > 64 {
+        value |= words[bit / 64 + 1] << (64 - bit % 64);
+    }
+    value & (u64::MAX >> (64 - width))
+}
+
+/// Distance between sampled positions used by [`select`].
+pub(crate) const SELECT_SAMPLE: usize = 256;
+
+/// Returns the position of every `SELECT_SAMPLE`-th set bit of `words`, or unset bit if `ones`
+/// is false, considering only the first `len` bits.
+pub(crate) fn select_samples(words: &[u64], len: usize, ones: bool) -> Box<[usize]> {
+    let mut samples = Vec::new();
+    let mut count = 0;
+    for bit in 0..len {
+        if (words[bit / 64] >> (bit % 64) & 1 == 1) == ones {
+            if count % SELECT_SAMPLE == 0 {
+                samples.push(bit);
+            }
+            count += 1;
+        }
+    }
+    samples.into_boxed_slice()
+}
+
+/// Returns the position of the `rank`-th set bit of `words`, or unset bit if `ones` is false,
+/// starting the scan from the sample taken by [`select_samples`].
+///
+/// The bit must exist.
+pub(crate) fn select(words: &[u64], samples: &[usize], rank: usize, ones: bool) -> usize {
+    let start = samples[rank / SELECT_SAMPLE];
+    let mut remaining = rank % SELECT_SAMPLE;
+    let mut word = start / 64;
+    let mut bits = if ones { words[word] } else { !words[word] } & (u64::MAX << (start % 64));
+    loop {
+        let count = bits.count_ones() as usize;
+        if remaining < count {
+            for _ in 0..remaining {
+                bits &= bits - 1;
+            }
+            return word * 64 + bits.trailing_zeros() as usize;
+        }
+        remaining -= count;
+        word += 1;
+        bits = if ones { words[word] } else { !words[word] };
+    }
+}
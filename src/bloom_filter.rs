@@ -0,0 +1,74 @@
+use std::{collections::hash_map::RandomState, fmt, hash::{BuildHasher, Hash}};
+
+/// A Bloom filter answering "definitely absent" or "maybe present" for a fixed set of keys.
+///
+/// Used by [`FilteredFrozenFlatMap`](crate::FilteredFrozenFlatMap) to reject missing keys
+/// without a binary search. With 10 bits per key about 1% of absent keys pass the filter.
+#[derive(Clone)]
+pub struct BloomFilter<S = RandomState> {
+    words: Box<[u64]>,
+    hashes: u32,
+    hasher: S,
+}
+
+impl<S> fmt::Debug for BloomFilter<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BloomFilter")
+         .field("bits", &(self.words.len() * 64))
+         .field("hashes", &self.hashes)
+         .finish()
+    }
+}
+
+impl BloomFilter {
+    pub fn from_keys<'a, K: Hash + 'a>(keys: impl ExactSizeIterator<Item=&'a K>, bits_per_key: usize) -> Self {
+        Self::from_keys_with_hasher(keys, bits_per_key, RandomState::new())
+    }
+}
+
+impl<S: BuildHasher> BloomFilter<S> {
+    pub fn from_keys_with_hasher<'a, K: Hash + 'a>(
+        keys: impl ExactSizeIterator<Item=&'a K>,
+        bits_per_key: usize,
+        hasher: S,
+    ) -> Self {
+        let bits = (keys.len() * bits_per_key).max(64);
+        let hashes = ((bits_per_key as f64 * std::f64::consts::LN_2).round() as u32).clamp(1, 16);
+        let mut filter = Self { words: vec![0; bits.div_ceil(64)].into_boxed_slice(), hashes, hasher };
+        for key in keys {
+            for bit in filter.bits(key) {
+                filter.words[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        filter
+    }
+
+    /// Returns `false` if `key` is definitely not in the set, `true` if it may be.
+    pub fn might_contain<Q: ?Sized + Hash>(&self, key: &Q) -> bool {
+        self.bits(key).all(|bit| self.words[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    // internals
+
+    /// Double hashing: the `i`-th probe is `h1 + i * h2`.
+    fn bits<Q: ?Sized + Hash>(&self, key: &Q) -> impl Iterator<Item=usize> {
+        let hash = self.hasher.hash_one(key);
+        let (h1, h2) = (hash, hash.rotate_left(32) | 1);
+        let len = self.words.len() as u64 * 64;
+        (0..self.hashes as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % len) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_might_contain() {
+        let keys = (0..1000).collect::<Vec<_>>();
+        let filter = BloomFilter::from_keys(keys.iter(), 10);
+        assert!(keys.iter().all(|k| filter.might_contain(k)));
+        let false_positives = (1000..11000).filter(|k| filter.might_contain(k)).count();
+        assert!(false_positives < 300, "{false_positives} false positives");
+    }
+}
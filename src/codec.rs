@@ -0,0 +1,170 @@
+//! A small versioned binary format for [`FrozenFlatMap`].
+//!
+//! Layout: the magic bytes `FLAT`, a `u16` format version, a `u64` entry count, then the
+//! entries in key order, each one as its key followed by its value. All integers are
+//! little-endian. How keys and values are encoded is up to their [`Codec`] implementation.
+
+use crate::FrozenFlatMap;
+use std::io::{self, Read, Write};
+
+const MAGIC: [u8; 4] = *b"FLAT";
+const VERSION: u16 = 1;
+
+/// Binary encoding of a single key or value.
+pub trait Codec: Sized {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>;
+
+    fn decode<R: Read>(r: &mut R) -> io::Result<Self>;
+}
+
+macro_rules! impl_codec_int {
+    ($($t:ty),*) => {$(
+        impl Codec for $t {
+            fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+                w.write_all(&self.to_le_bytes())
+            }
+
+            fn decode<R: Read>(r: &mut R) -> io::Result<Self> {
+                let mut bytes = [0; size_of::<$t>()];
+                r.read_exact(&mut bytes)?;
+                Ok(<$t>::from_le_bytes(bytes))
+            }
+        }
+    )*};
+}
+
+impl_codec_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl Codec for () {
+    fn encode<W: Write>(&self, _: &mut W) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn decode<R: Read>(_: &mut R) -> io::Result<Self> {
+        Ok(())
+    }
+}
+
+impl Codec for bool {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        (*self as u8).encode(w)
+    }
+
+    fn decode<R: Read>(r: &mut R) -> io::Result<Self> {
+        match u8::decode(r)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(invalid_data("invalid bool")),
+        }
+    }
+}
+
+impl Codec for char {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        (*self as u32).encode(w)
+    }
+
+    fn decode<R: Read>(r: &mut R) -> io::Result<Self> {
+        char::from_u32(u32::decode(r)?).ok_or_else(|| invalid_data("invalid char"))
+    }
+}
+
+/// Length-prefixed UTF-8.
+impl Codec for String {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        (self.len() as u64).encode(w)?;
+        w.write_all(self.as_bytes())
+    }
+
+    fn decode<R: Read>(r: &mut R) -> io::Result<Self> {
+        let len = u64::decode(r)?;
+        let mut bytes = Vec::new();
+        r.take(len).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        String::from_utf8(bytes).map_err(|_| invalid_data("invalid UTF-8"))
+    }
+}
+
+/// Length-prefixed sequence of elements.
+impl<T: Codec> Codec for Vec<T> {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        (self.len() as u64).encode(w)?;
+        self.iter().try_for_each(|item| item.encode(w))
+    }
+
+    fn decode<R: Read>(r: &mut R) -> io::Result<Self> {
+        let len = u64::decode(r)?;
+        (0..len).map(|_| T::decode(r)).collect()
+    }
+}
+
+impl<K: Ord + Codec, V: Codec> FrozenFlatMap<K, V> {
+    /// Writes the map in the format described in the [module documentation](crate::codec).
+    pub fn write_to<W: Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(&MAGIC)?;
+        VERSION.encode(&mut w)?;
+        (self.len() as u64).encode(&mut w)?;
+        for (key, value) in self {
+            key.encode(&mut w)?;
+            value.encode(&mut w)?;
+        }
+        w.flush()
+    }
+
+    /// Reads a map written by [`FrozenFlatMap::write_to`], verifying the key order.
+    pub fn read_from<R: Read>(mut r: R) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(invalid_data("not a flat_collections file"));
+        }
+        let version = u16::decode(&mut r)?;
+        if version != VERSION {
+            return Err(invalid_data(format!("unsupported format version {version}")));
+        }
+
+        let len = u64::decode(&mut r)?;
+        let items = (0..len)
+            .map(|_| Ok((K::decode(&mut r)?, V::decode(&mut r)?)))
+            .collect::<io::Result<Vec<_>>>()?;
+        Self::try_from_sorted(items.into_boxed_slice()).map_err(invalid_data)
+    }
+}
+
+pub(crate) fn invalid_data(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let m = FrozenFlatMap::from([("b".to_string(), vec![1u8, 2]), ("a".to_string(), vec![])]);
+        let mut bytes = Vec::new();
+        m.write_to(&mut bytes).unwrap();
+        assert_eq!(&bytes[..6], b"FLAT\x01\x00");
+        assert_eq!(FrozenFlatMap::read_from(bytes.as_slice()).unwrap(), m);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let mut bytes = Vec::new();
+        FrozenFlatMap::from([(1u32, 'a'), (2, 'b')]).write_to(&mut bytes).unwrap();
+
+        let read = |bytes: &[u8]| FrozenFlatMap::<u32, char>::read_from(bytes).unwrap_err().kind();
+        assert_eq!(read(&bytes[..bytes.len() - 1]), io::ErrorKind::UnexpectedEof);
+        assert_eq!(read(b"JUNK\x01\x00"), io::ErrorKind::InvalidData);
+
+        let mut newer = bytes.clone();
+        newer[4] = 2;
+        assert_eq!(read(&newer), io::ErrorKind::InvalidData);
+
+        let mut unsorted = bytes.clone();
+        unsorted[14..18].copy_from_slice(&3u32.to_le_bytes());
+        assert_eq!(read(&unsorted), io::ErrorKind::InvalidData);
+    }
+}
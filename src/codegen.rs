@@ -0,0 +1,84 @@
+//! Helpers for `build.rs` scripts that generate static lookup tables.
+//!
+//! The functions return a Rust expression, the caller writes the declaration around it:
+//!
+//! ```ignore
+//! let keywords = [("fn", 0), ("let", 1), ("struct", 2)];
+//! writeln!(
+//!     out,
+//!     "static KEYWORDS: flat_collections::FlatMapView<&str, u32> = {};",
+//!     flat_collections::codegen::map_expr(keywords),
+//! )?;
+//! ```
+//!
+//! Both generators emit a `'static` [`FlatMapView`](crate::FlatMapView) expression rather than
+//! a [`FrozenFlatMap`](crate::FrozenFlatMap), which is heap-backed and cannot be a `static`.
+//!
+//! Keys and values are emitted with their [`Debug`] representation, which is valid Rust for
+//! integers, `char`s, `bool`s and strings. Wrap values in [`Code`] to emit arbitrary source.
+
+use crate::{FlatMap, FlatSet};
+use std::fmt::{self, Debug, Display, Write};
+
+/// Emits the wrapped source verbatim in place of a `Debug` representation.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Code<T>(pub T);
+
+impl<T: Display> Debug for Code<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Returns an expression of type `FlatMapView<'static, K, V>` holding `entries` sorted by key.
+///
+/// If there are duplicates, the last one is kept.
+pub fn map_expr<K: Ord + Debug, V: Debug>(entries: impl IntoIterator<Item=(K, V)>) -> String {
+    let map = entries.into_iter().collect::<FlatMap<_, _>>();
+    let mut out = String::from("::flat_collections::FlatMapView::from_sorted_unchecked(&[\n");
+    for (key, value) in &map {
+        writeln!(out, "    ({key:?}, {value:?}),").unwrap();
+    }
+    out.push_str("])");
+    out
+}
+
+/// Returns an expression of type `FlatMapView<'static, K, ()>` holding `keys` sorted and
+/// deduplicated, for use as a static set with `contains_key` and `range`.
+pub fn set_expr<K: Ord + Debug>(keys: impl IntoIterator<Item=K>) -> String {
+    let set = FlatSet::from_iter(keys);
+    let mut out = String::from("::flat_collections::FlatMapView::from_sorted_unchecked(&[\n");
+    for key in &set {
+        writeln!(out, "    ({key:?}, ()),").unwrap();
+    }
+    out.push_str("])");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_expr() {
+        let expr = map_expr([("b", 2), ("a\"", 1), ("b", 3)]);
+        assert_eq!(
+            expr,
+            "::flat_collections::FlatMapView::from_sorted_unchecked(&[\n    (\"a\\\"\", 1),\n    (\"b\", 3),\n])"
+        );
+    }
+
+    #[test]
+    fn test_code_values() {
+        let expr = map_expr([('x', Code("Axis::X")), ('y', Code("Axis::Y"))]);
+        assert!(expr.contains("('x', Axis::X),"));
+    }
+
+    #[test]
+    fn test_set_expr() {
+        assert_eq!(
+            set_expr([3, 1, 3, -2]),
+            "::flat_collections::FlatMapView::from_sorted_unchecked(&[\n    (-2, ()),\n    (1, ()),\n    (3, ()),\n])"
+        );
+    }
+}
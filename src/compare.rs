@@ -0,0 +1,76 @@
+//! Traits describing how keys are ordered and compared against lookup keys.
+
+use std::cmp::Ordering;
+#[cfg(not(feature = "equivalent"))]
+use std::borrow::Borrow;
+
+/// Key comparison used by all lookups.
+///
+/// Implemented for every `Q: Ord` that `K` borrows as, just like `Borrow`-based lookups
+/// in `BTreeMap`. With the `equivalent` feature it is implemented for every
+/// [`equivalent::Comparable`] instead, so custom query types such as `(&str, u32)` for
+/// `(String, u32)` keys can be used without allocating a key.
+pub trait Comparable<K: ?Sized> {
+    /// Compares `self` to `key` and returns their ordering.
+    fn compare(&self, key: &K) -> Ordering;
+}
+
+#[cfg(not(feature = "equivalent"))]
+impl<Q: ?Sized + Ord, K: ?Sized + Borrow<Q>> Comparable<K> for Q {
+    fn compare(&self, key: &K) -> Ordering {
+        Ord::cmp(self, key.borrow())
+    }
+}
+
+#[cfg(feature = "equivalent")]
+impl<Q: ?Sized + equivalent::Comparable<K>, K: ?Sized> Comparable<K> for Q {
+    fn compare(&self, key: &K) -> Ordering {
+        equivalent::Comparable::compare(self, key)
+    }
+}
+
+/// Defines the order of keys in a [`FlatMap`](crate::FlatMap).
+///
+/// `Compare<K>` orders stored keys among themselves, `Compare<K, Q>` additionally allows
+/// looking them up by `&Q`. Plain functions and closures of the right shape implement it too.
+pub trait Compare<L: ?Sized, R: ?Sized = L> {
+    /// Compares `l` to `r` and returns their ordering.
+    fn compare(&self, l: &L, r: &R) -> Ordering;
+
+    /// Finds `key` in `items` sorted by `self`, with the same result as [`slice::binary_search`].
+    ///
+    /// Exact lookups go through this method, so overriding it changes the search algorithm,
+    /// see [`Branchless`](crate::Branchless) and [`Interpolation`](crate::Interpolation).
+    fn search<V>(&self, items: &[(L, V)], key: &R) -> Result<usize, usize>
+    where
+        L: Sized,
+    {
+        items.binary_search_by(|probe| self.compare(&probe.0, key))
+    }
+}
+
+/// Orders keys by their [`Ord`] implementation, the default for [`FlatMap`](crate::FlatMap).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NaturalOrder;
+
+impl<K: ?Sized, Q: ?Sized + Comparable<K>> Compare<K, Q> for NaturalOrder {
+    fn compare(&self, key: &K, query: &Q) -> Ordering {
+        query.compare(key).reverse()
+    }
+}
+
+/// Reverses another comparator, e.g. `Reversed<NaturalOrder>` keeps keys in descending order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Reversed<C = NaturalOrder>(pub C);
+
+impl<L: ?Sized, R: ?Sized, C: Compare<L, R>> Compare<L, R> for Reversed<C> {
+    fn compare(&self, l: &L, r: &R) -> Ordering {
+        self.0.compare(l, r).reverse()
+    }
+}
+
+impl<L: ?Sized, R: ?Sized, F: Fn(&L, &R) -> Ordering> Compare<L, R> for F {
+    fn compare(&self, l: &L, r: &R) -> Ordering {
+        self(l, r)
+    }
+}
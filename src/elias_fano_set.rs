@@ -0,0 +1,253 @@
+use crate::{bits, PackedKey};
+use std::{fmt, iter::FusedIterator, marker::PhantomData};
+
+/// Immutable set of integers in Elias-Fano encoding.
+///
+/// Every key is split into low bits, stored bit-packed, and high bits, stored in unary in a
+/// bit vector. This takes less than `2 + log2(max / len)` bits per key, close to the
+/// theoretical minimum, while [`EliasFanoSet::select`] is O(1) and [`EliasFanoSet::contains`]
+/// and [`EliasFanoSet::rank`] are O(log(max / len)). A typical use are posting lists and ID sets.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct EliasFanoSet<K: PackedKey> {
+    len: usize,
+    low_width: usize,
+    low: Box<[u64]>,
+    /// For the `i`-th key the bit at `i + (key >> low_width)` is set.
+    high: Box<[u64]>,
+    high_len: usize,
+    ones: Box<[usize]>,
+    zeros: Box<[usize]>,
+    marker: PhantomData<K>,
+}
+
+impl<K: PackedKey + fmt::Debug> fmt::Debug for EliasFanoSet<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PackedKey> Default for EliasFanoSet<K> {
+    fn default() -> Self {
+        Self::from(Vec::new())
+    }
+}
+
+impl<K: PackedKey> From<Vec<K>> for EliasFanoSet<K> {
+    fn from(mut keys: Vec<K>) -> Self {
+        keys.sort_unstable();
+        keys.dedup();
+        let keys = keys.into_iter().map(K::to_u64).collect::<Vec<_>>();
+
+        let len = keys.len();
+        let max = keys.last().copied().unwrap_or(0);
+        let low_width = (max / len.max(1) as u64).checked_ilog2().unwrap_or(0) as usize;
+        let high_len = len + (max >> low_width) as usize + 1;
+
+        let mut low = vec![0; bits::words_for(len, low_width)];
+        let mut high = vec![0; high_len.div_ceil(64)];
+        for (i, &key) in keys.iter().enumerate() {
+            bits::pack(&mut low, i, low_width, key);
+            let bit = i + (key >> low_width) as usize;
+            high[bit / 64] |= 1 << (bit % 64);
+        }
+
+        Self {
+            len,
+            low_width,
+            ones: bits::select_samples(&high, high_len, true),
+            zeros: bits::select_samples(&high, high_len, false),
+            low: low.into_boxed_slice(),
+            high: high.into_boxed_slice(),
+            high_len,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<K: PackedKey, const N: usize> From<[K; N]> for EliasFanoSet<K> {
+    fn from(value: [K; N]) -> Self {
+        Self::from(Vec::from(value))
+    }
+}
+
+impl<K: PackedKey> FromIterator<K> for EliasFanoSet<K> {
+    fn from_iter<I: IntoIterator<Item=K>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<'a, K: PackedKey> IntoIterator for &'a EliasFanoSet<K> {
+    type Item = K;
+    type IntoIter = Iter<'a, K>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: PackedKey> EliasFanoSet<K> {
+    // lookup
+
+    pub fn contains(&self, key: &K) -> bool {
+        let index = self.rank(key);
+        self.select(index) == Some(*key)
+    }
+
+    // positional access
+
+    /// Returns the number of keys strictly less than `key`.
+    pub fn rank(&self, key: &K) -> usize {
+        let key = key.to_u64();
+        let high = (key >> self.low_width) as usize;
+        let low = key & ((1 << self.low_width) - 1);
+        if high >= self.high_len - self.len {
+            return self.len;
+        }
+
+        // the keys with this high part are the set bits following the `high - 1`-th unset bit
+        let mut bit = match high {
+            0 => 0,
+            _ => bits::select(&self.high, &self.zeros, high - 1, false) + 1,
+        };
+        let mut index = bit - high;
+        while self.high[bit / 64] >> (bit % 64) & 1 == 1 && self.low(index) < low {
+            bit += 1;
+            index += 1;
+        }
+        index
+    }
+
+    /// Returns the key with exactly `rank` smaller keys, the inverse of [`EliasFanoSet::rank`].
+    pub fn select(&self, rank: usize) -> Option<K> {
+        if rank >= self.len {
+            return None;
+        }
+        let high = bits::select(&self.high, &self.ones, rank, true) - rank;
+        Some(self.key(rank, high))
+    }
+
+    // misc
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn first(&self) -> Option<K> {
+        self.select(0)
+    }
+
+    pub fn last(&self) -> Option<K> {
+        self.select(self.len.checked_sub(1)?)
+    }
+
+    /// Returns the number of bytes used to store the encoded keys.
+    pub fn key_bytes(&self) -> usize {
+        (self.low.len() + self.high.len()) * 8 + (self.ones.len() + self.zeros.len()) * size_of::<usize>()
+    }
+
+    // iterators
+
+    pub fn iter(&self) -> Iter<'_, K> {
+        Iter { set: self, index: 0, bit: 0 }
+    }
+
+    // internals
+
+    fn low(&self, index: usize) -> u64 {
+        bits::unpack(&self.low, index, self.low_width)
+    }
+
+    fn key(&self, index: usize, high: usize) -> K {
+        K::from_u64((high as u64) << self.low_width | self.low(index))
+    }
+}
+
+/// An iterator over the keys of an [`EliasFanoSet`] in order.
+#[derive(Clone)]
+pub struct Iter<'a, K: PackedKey> {
+    set: &'a EliasFanoSet<K>,
+    index: usize,
+    /// Position in the high bits to continue scanning from.
+    bit: usize,
+}
+
+impl<K: PackedKey> Iterator for Iter<'_, K> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.set.len {
+            return None;
+        }
+        let mut word = self.bit / 64;
+        let mut bits = self.set.high[word] & (u64::MAX << (self.bit % 64));
+        while bits == 0 {
+            word += 1;
+            bits = self.set.high[word];
+        }
+        let bit = word * 64 + bits.trailing_zeros() as usize;
+
+        let key = self.set.key(self.index, bit - self.index);
+        self.index += 1;
+        self.bit = bit + 1;
+        Some(key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.set.len - self.index;
+        (len, Some(len))
+    }
+}
+
+impl<K: PackedKey> ExactSizeIterator for Iter<'_, K> {}
+
+impl<K: PackedKey> FusedIterator for Iter<'_, K> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let keys = (0..5000u64).map(|i| i * i % 100_003).collect::<Vec<_>>();
+        let set = keys.iter().copied().collect::<EliasFanoSet<_>>();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        sorted.dedup();
+
+        assert_eq!(set.len(), sorted.len());
+        assert_eq!(set.iter().collect::<Vec<_>>(), sorted);
+        for (i, key) in sorted.iter().enumerate() {
+            assert!(set.contains(key));
+            assert_eq!(set.rank(key), i);
+            assert_eq!(set.select(i), Some(*key));
+            assert!(!set.contains(&(key + 100_003)));
+        }
+        assert_eq!(set.rank(&1), 1);
+        assert_eq!(set.rank(&u64::MAX), set.len());
+        assert!(set.key_bytes() < sorted.len() * 8 / 2);
+    }
+
+    #[test]
+    fn test_extremes() {
+        let set = EliasFanoSet::from([i32::MAX, i32::MIN, 0, -1, i32::MIN]);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![i32::MIN, -1, 0, i32::MAX]);
+        assert_eq!(set.first(), Some(i32::MIN));
+        assert_eq!(set.last(), Some(i32::MAX));
+        assert!(set.contains(&-1));
+        assert!(!set.contains(&1));
+        assert_eq!(set.select(4), None);
+
+        let set = EliasFanoSet::from([0u64, u64::MAX]);
+        assert!(set.contains(&u64::MAX));
+        assert_eq!(set.rank(&(u64::MAX - 1)), 1);
+
+        let empty = EliasFanoSet::<u8>::default();
+        assert!(!empty.contains(&0));
+        assert_eq!(empty.rank(&0), 0);
+        assert_eq!((&empty).into_iter().next(), None);
+    }
+}
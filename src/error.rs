@@ -0,0 +1,94 @@
+use std::{error::Error, fmt};
+
+/// The input passed to a `try_from_sorted` constructor was not strictly increasing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NotSortedError {
+    pub(crate) index: usize,
+}
+
+impl NotSortedError {
+    /// Index of the first element that is not greater than its predecessor.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl fmt::Display for NotSortedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "input is not strictly sorted at index {}", self.index)
+    }
+}
+
+impl Error for NotSortedError {}
+
+/// A key inserted through a cursor would break the ordering of the map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnorderedKeyError;
+
+impl fmt::Display for UnorderedKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("key is not properly ordered relative to its neighbors")
+    }
+}
+
+impl Error for UnorderedKeyError {}
+
+/// Construction with [`DuplicatePolicy::Error`](crate::flat_map::DuplicatePolicy::Error)
+/// encountered the same key twice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DuplicateKeyError<K> {
+    pub(crate) key: K,
+}
+
+impl<K> DuplicateKeyError<K> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+}
+
+impl<K: fmt::Debug> fmt::Display for DuplicateKeyError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "duplicate key {:?}", self.key)
+    }
+}
+
+impl<K: fmt::Debug> Error for DuplicateKeyError<K> {}
+
+/// The key ranges of the maps passed to [`FlatMap::concat`](crate::FlatMap::concat) overlap.
+///
+/// Gives the untouched inputs back through [`OverlapError::into_parts`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OverlapError<M> {
+    pub(crate) lower: M,
+    pub(crate) upper: M,
+}
+
+impl<M> OverlapError<M> {
+    pub fn into_parts(self) -> (M, M) {
+        (self.lower, self.upper)
+    }
+}
+
+impl<M> fmt::Display for OverlapError<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("key ranges overlap")
+    }
+}
+
+impl<M: fmt::Debug> Error for OverlapError<M> {}
+
+/// A key pushed into a [`FlatMapBuilder`](crate::FlatMapBuilder) was not greater than the previous one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutOfOrderError;
+
+impl fmt::Display for OutOfOrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("key is not greater than the previously pushed key")
+    }
+}
+
+impl Error for OutOfOrderError {}
@@ -0,0 +1,101 @@
+use crate::{BloomFilter, Comparable, FrozenFlatMap};
+use std::{borrow::Borrow, collections::hash_map::RandomState, fmt, hash::{BuildHasher, Hash}, ops::Deref};
+
+/// A [`FrozenFlatMap`] with a [`BloomFilter`] in front of it, built by
+/// [`FrozenFlatMap::with_bloom_filter`].
+///
+/// Lookups of absent keys are usually rejected by the filter without touching the entries.
+/// Everything else goes to the sorted entries, which it derefs to.
+pub struct FilteredFrozenFlatMap<K: Ord, V, S = RandomState> {
+    map: FrozenFlatMap<K, V>,
+    filter: BloomFilter<S>,
+}
+
+impl<K: Ord + Clone, V: Clone, S: Clone> Clone for FilteredFrozenFlatMap<K, V, S> {
+    fn clone(&self) -> Self {
+        Self { map: self.map.clone(), filter: self.filter.clone() }
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug, S> fmt::Debug for FilteredFrozenFlatMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<K: Ord, V, S> Deref for FilteredFrozenFlatMap<K, V, S> {
+    type Target = FrozenFlatMap<K, V>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl<K: Ord + Hash, V> FrozenFlatMap<K, V> {
+    /// Builds a Bloom filter over the keys with `bits_per_key` bits of memory per entry.
+    pub fn with_bloom_filter(self, bits_per_key: usize) -> FilteredFrozenFlatMap<K, V> {
+        self.with_bloom_filter_and_hasher(bits_per_key, RandomState::new())
+    }
+
+    pub fn with_bloom_filter_and_hasher<S: BuildHasher>(
+        self,
+        bits_per_key: usize,
+        hasher: S,
+    ) -> FilteredFrozenFlatMap<K, V, S> {
+        let filter = BloomFilter::from_keys_with_hasher(self.keys(), bits_per_key, hasher);
+        FilteredFrozenFlatMap { map: self, filter }
+    }
+}
+
+impl<K: Ord + Hash, V, S: BuildHasher> FilteredFrozenFlatMap<K, V, S> {
+    pub fn filter(&self) -> &BloomFilter<S> {
+        &self.filter
+    }
+
+    /// Drops the filter.
+    pub fn into_frozen(self) -> FrozenFlatMap<K, V> {
+        self.map
+    }
+
+    // lookup
+
+    pub fn contains_key<Q: ?Sized + Hash + Comparable<K>>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.filter.might_contain(key) && self.map.contains_key(key)
+    }
+
+    pub fn get<Q: ?Sized + Hash + Comparable<K>>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    pub fn get_key_value<Q: ?Sized + Hash + Comparable<K>>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+    {
+        if !self.filter.might_contain(key) {
+            return None;
+        }
+        self.map.get_key_value(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get() {
+        let m = (0..100).map(|i| (i.to_string(), i)).collect::<FrozenFlatMap<_, _>>().with_bloom_filter(10);
+        for i in 0..100 {
+            assert_eq!(m.get(i.to_string().as_str()), Some(&i));
+        }
+        assert!((100..200).all(|i| !m.contains_key(i.to_string().as_str())));
+        assert_eq!(m.range("5".to_string()..).count(), 55);
+        assert_eq!(m.into_frozen().len(), 100);
+    }
+}
@@ -0,0 +1,233 @@
+use crate::Comparable;
+use std::{cmp::Ordering, fmt, ops::Range};
+
+/// A map from disjoint half-open ranges of keys to values, backed by a sorted `Vec` of
+/// boundaries.
+///
+/// The value at index `i` covers `bounds[i]..bounds[i + 1]`, and gaps between ranges are
+/// stored as `None`. Adjacent ranges with equal values are merged, so two maps covering the
+/// same keys with the same values compare equal.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct FlatIntervalMap<K, V> {
+    bounds: Vec<K>,
+    values: Vec<Option<V>>,
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for FlatIntervalMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Inserts the ranges in order, so later ranges overwrite earlier ones where they overlap.
+impl<K: Ord, V: Clone + PartialEq> FromIterator<(Range<K>, V)> for FlatIntervalMap<K, V> {
+    fn from_iter<I: IntoIterator<Item=(Range<K>, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (range, value) in iter {
+            map.insert(range, value);
+        }
+        map
+    }
+}
+
+impl<K, V> Default for FlatIntervalMap<K, V> {
+    fn default() -> Self {
+        Self { bounds: Vec::new(), values: Vec::new() }
+    }
+}
+
+impl<K: Ord, V> FlatIntervalMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // lookup
+
+    /// Returns the value of the range containing `key`, in O(log n).
+    pub fn get<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<&V> {
+        self.get_interval(key).map(|(_, v)| v)
+    }
+
+    /// Returns the range containing `key` together with its value.
+    pub fn get_interval<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<(Range<&K>, &V)> {
+        let i = self.bounds.partition_point(|b| key.compare(b) != Ordering::Less).checked_sub(1)?;
+        let value = self.values.get(i)?.as_ref()?;
+        Some((&self.bounds[i]..&self.bounds[i + 1], value))
+    }
+
+    pub fn contains_key<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> bool {
+        self.get(key).is_some()
+    }
+
+    // modification
+
+    /// Maps every key in `range` to `value`, splitting the ranges it partially overlaps and
+    /// merging it with neighbours that have an equal value, in O(n).
+    ///
+    /// Empty ranges are ignored.
+    pub fn insert(&mut self, range: Range<K>, value: V)
+    where
+        V: Clone + PartialEq,
+    {
+        self.assign(range, Some(value));
+    }
+
+    /// Unmaps every key in `range`, splitting the ranges it partially overlaps, in O(n).
+    pub fn remove(&mut self, range: Range<K>)
+    where
+        V: Clone + PartialEq,
+    {
+        self.assign(range, None);
+    }
+
+    // misc
+
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.bounds.clear();
+        self.values.clear();
+    }
+
+    // iterators
+
+    /// Iterates over the ranges in order, together with their values.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item=(Range<&K>, &V)> + '_ {
+        self.bounds
+            .windows(2)
+            .zip(&self.values)
+            .filter_map(|(w, v)| Some((&w[0]..&w[1], v.as_ref()?)))
+    }
+
+    // internals
+
+    /// Makes `at` a boundary, splitting the range containing it in two, and returns its index.
+    fn split(&mut self, at: K) -> usize
+    where
+        V: Clone,
+    {
+        match self.bounds.binary_search(&at) {
+            Ok(i) => i,
+            Err(i) => {
+                if !self.bounds.is_empty() {
+                    let outside = i == 0 || i == self.bounds.len();
+                    let value = if outside { None } else { self.values[i - 1].clone() };
+                    self.values.insert(i.min(self.values.len()), value);
+                }
+                self.bounds.insert(i, at);
+                i
+            }
+        }
+    }
+
+    fn assign(&mut self, range: Range<K>, value: Option<V>)
+    where
+        V: Clone + PartialEq,
+    {
+        if range.start >= range.end {
+            return;
+        }
+
+        let start = self.split(range.start);
+        let end = self.split(range.end);
+        self.bounds.drain(start + 1..end);
+        self.values.splice(start..end, [value]);
+
+        if self.values.get(start + 1) == Some(&self.values[start]) {
+            self.bounds.remove(start + 1);
+            self.values.remove(start + 1);
+        }
+        if start > 0 && self.values[start - 1] == self.values[start] {
+            self.bounds.remove(start);
+            self.values.remove(start);
+        }
+
+        while self.values.first().is_some_and(Option::is_none) {
+            self.bounds.remove(0);
+            self.values.remove(0);
+        }
+        while self.values.last().is_some_and(Option::is_none) {
+            self.bounds.pop();
+            self.values.pop();
+        }
+        if self.values.is_empty() {
+            self.bounds.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intervals(m: &FlatIntervalMap<u32, char>) -> Vec<(u32, u32, char)> {
+        m.iter().map(|(r, &v)| (*r.start, *r.end, v)).collect()
+    }
+
+    #[test]
+    fn test_get() {
+        let m = FlatIntervalMap::from_iter([(10..20, 'a'), (30..40, 'b')]);
+        assert_eq!(m.get(&9), None);
+        assert_eq!(m.get(&10), Some(&'a'));
+        assert_eq!(m.get(&19), Some(&'a'));
+        assert_eq!(m.get(&20), None);
+        assert_eq!(m.get(&35), Some(&'b'));
+        assert_eq!(m.get(&40), None);
+        assert_eq!(m.get_interval(&12), Some((&10..&20, &'a')));
+        assert!(m.contains_key(&30));
+        assert!(!m.contains_key(&25));
+    }
+
+    #[test]
+    fn test_insert_split() {
+        let mut m = FlatIntervalMap::new();
+        m.insert(0..100, 'a');
+        m.insert(40..60, 'b');
+        assert_eq!(intervals(&m), vec![(0, 40, 'a'), (40, 60, 'b'), (60, 100, 'a')]);
+
+        m.insert(30..70, 'c');
+        assert_eq!(intervals(&m), vec![(0, 30, 'a'), (30, 70, 'c'), (70, 100, 'a')]);
+
+        m.insert(150..200, 'd');
+        m.insert(120..130, 'e');
+        assert_eq!(m.get(&110), None);
+        assert_eq!(m.get(&125), Some(&'e'));
+        assert_eq!(format!("{m:?}"), "{0..30: 'a', 30..70: 'c', 70..100: 'a', 120..130: 'e', 150..200: 'd'}");
+    }
+
+    #[test]
+    fn test_insert_merge() {
+        let mut m = FlatIntervalMap::from_iter([(0..10, 'a'), (20..30, 'a')]);
+        m.insert(10..20, 'a');
+        assert_eq!(intervals(&m), vec![(0, 30, 'a')]);
+
+        m.insert(5..25, 'b');
+        m.insert(5..25, 'a');
+        assert_eq!(m, FlatIntervalMap::from_iter([(0..30, 'a')]));
+
+        m.insert(5..5, 'b');
+        assert_eq!(intervals(&m), vec![(0, 30, 'a')]);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut m = FlatIntervalMap::from_iter([(0..100, 'a')]);
+        m.remove(40..60);
+        assert_eq!(intervals(&m), vec![(0, 40, 'a'), (60, 100, 'a')]);
+        m.remove(50..200);
+        assert_eq!(intervals(&m), vec![(0, 40, 'a')]);
+        m.insert(40..50, 'a');
+        assert_eq!(intervals(&m), vec![(0, 50, 'a')]);
+        m.remove(0..50);
+        assert!(m.is_empty());
+        assert_eq!(m, FlatIntervalMap::new());
+
+        m.remove(0..10);
+        assert!(m.is_empty());
+        m.insert(0..10, 'a');
+        m.clear();
+        assert!(m.is_empty());
+    }
+}
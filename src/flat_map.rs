@@ -1,4 +1,15 @@
-use std::{cmp::Ordering, mem, ops::{Bound, RangeBounds}};
+mod builder;
+mod cursor;
+mod entry;
+mod iter;
+
+pub use builder::FlatMapBuilder;
+pub use cursor::{Cursor, CursorMut};
+pub use entry::OccupiedEntry;
+pub use iter::{IntoIter, Iter, IterMut, Keys, Values, ValuesMut};
+
+use crate::{sorted, Compare, DuplicateKeyError, FlatSet, FrozenFlatMap, NaturalOrder, NotSortedError, OverlapError, Storage, StorageMut};
+use std::{cmp::Ordering, collections::{BTreeMap, HashMap}, fmt, hash::{BuildHasher, Hash, Hasher}, marker::PhantomData, mem, ops::{Index, IndexMut, Range, RangeBounds}, vec};
 
 /// Memory-efficient map backed by a contiguous flat array.
 ///
@@ -11,17 +22,35 @@ use std::{cmp::Ordering, mem, ops::{Bound, RangeBounds}};
 /// | remove    | O(n)    | O(n)    | O(1)    |
 ///
 /// Insert and remove work in O(1) if you are dealing with last element.
-pub struct FlatMap<K: Ord, V> {
-    items: Vec<(K, V)>,
+///
+/// Keys are ordered by the comparator `C`, which defaults to their [`Ord`] implementation.
+/// See [`Compare`] for custom orders.
+///
+/// Entries are kept in `S`, a `Vec` by default. Other [`Storage`] types support the lookup
+/// API, and [`StorageMut`] types additionally insertion and removal.
+pub struct FlatMap<K, V, C = NaturalOrder, S = Vec<(K, V)>> {
+    items: S,
+    cmp: C,
+    marker: PhantomData<(K, V)>,
+}
+
+/// What to do with entries sharing a key when building a map from unsorted input.
+#[derive(Clone, Copy, Debug)]
+pub enum DuplicatePolicy<V> {
+    /// Keep the entry that comes first in the input.
+    KeepFirst,
+    /// Keep the entry that comes last in the input, like `FlatMap::from` does.
+    KeepLast,
+    /// Fail with [`DuplicateKeyError`].
+    Error,
+    /// Combine the values in input order, keeping the first key.
+    Merge(fn(V, V) -> V),
 }
 
 /// If there are duplicates, the last one is kept.
 impl<K: Ord, V> From<Vec<(K, V)>> for FlatMap<K, V> {
-    fn from(mut items: Vec<(K, V)>) -> Self {
-        items.reverse();
-        items.sort_by(|a, b| K::cmp(&a.0, &b.0));
-        items.dedup_by(|a, b| K::eq(&a.0, &b.0));
-        FlatMap { items }
+    fn from(items: Vec<(K, V)>) -> Self {
+        Self::from_vec_with_comparator(items, NaturalOrder)
     }
 }
 
@@ -37,84 +66,581 @@ impl<K: Ord + Clone, V: Clone, const N: usize> From<[(K, V); N]> for FlatMap<K,
     }
 }
 
-impl<K: Ord, V> FromIterator<(K, V)> for FlatMap<K, V> {
+/// Takes over the already sorted entries in O(n).
+impl<K: Ord, V> From<BTreeMap<K, V>> for FlatMap<K, V> {
+    fn from(map: BTreeMap<K, V>) -> Self {
+        Self::from_sorted_vec_unchecked(map.into_iter().collect())
+    }
+}
+
+impl<K: Ord, V, S> From<HashMap<K, V, S>> for FlatMap<K, V> {
+    fn from(map: HashMap<K, V, S>) -> Self {
+        let mut items = map.into_iter().collect::<Vec<_>>();
+        items.sort_unstable_by(|a, b| K::cmp(&a.0, &b.0));
+        Self::from_sorted_vec_unchecked(items)
+    }
+}
+
+impl<K: Ord, V, C> From<FlatMap<K, V, C>> for BTreeMap<K, V> {
+    fn from(map: FlatMap<K, V, C>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<K: Eq + Hash, V, C, S: BuildHasher + Default> From<FlatMap<K, V, C>> for HashMap<K, V, S> {
+    fn from(map: FlatMap<K, V, C>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<K, V, C: Compare<K> + Default> FromIterator<(K, V)> for FlatMap<K, V, C> {
     fn from_iter<I: IntoIterator<Item=(K, V)>>(iter: I) -> Self {
-        Self::from(iter.into_iter().collect::<Vec<_>>())
+        Self::from_vec_with_comparator(iter.into_iter().collect(), C::default())
+    }
+}
+
+/// See [`FlatMap::insert_many`].
+impl<K, V, C: Compare<K>> Extend<(K, V)> for FlatMap<K, V, C> {
+    fn extend<I: IntoIterator<Item=(K, V)>>(&mut self, iter: I) {
+        self.insert_many(iter);
+    }
+}
+
+impl<'a, K: Copy, V: Copy, C: Compare<K>> Extend<(&'a K, &'a V)> for FlatMap<K, V, C> {
+    fn extend<I: IntoIterator<Item=(&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(k, v)| (*k, *v)));
+    }
+}
+
+/// Panics if the key is not present in the map.
+impl<K, V, C, S, Q> Index<&Q> for FlatMap<K, V, C, S>
+where
+    C: Compare<K> + Compare<K, Q>,
+    S: Storage<(K, V)>,
+    Q: ?Sized,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+/// Panics if the key is not present in the map.
+impl<K, V, C, S, Q> IndexMut<&Q> for FlatMap<K, V, C, S>
+where
+    C: Compare<K> + Compare<K, Q>,
+    S: Storage<(K, V)>,
+    Q: ?Sized,
+{
+    fn index_mut(&mut self, key: &Q) -> &mut V {
+        self.get_mut(key).expect("no entry found for key")
+    }
+}
+
+impl<K, V, C> IntoIterator for FlatMap<K, V, C> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { inner: self.items.into_iter() }
+    }
+}
+
+impl<'a, K, V, C, S: Storage<(K, V)>> IntoIterator for &'a FlatMap<K, V, C, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Iter { inner: self.items.as_slice().iter() }
+    }
+}
+
+impl<'a, K, V, C, S: Storage<(K, V)>> IntoIterator for &'a mut FlatMap<K, V, C, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IterMut { inner: self.items.as_mut_slice().iter_mut() }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, C, S: Storage<(K, V)>> fmt::Debug for FlatMap<K, V, C, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K, V, C: Clone, S: Clone> Clone for FlatMap<K, V, C, S> {
+    fn clone(&self) -> Self {
+        Self::from_parts(self.items.clone(), self.cmp.clone())
+    }
+}
+
+/// Maps are compared by their entries only.
+impl<K: PartialEq, V: PartialEq, C, S: Storage<(K, V)>> PartialEq for FlatMap<K, V, C, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.items.as_slice() == other.items.as_slice()
+    }
+}
+
+impl<K: Eq, V: Eq, C, S: Storage<(K, V)>> Eq for FlatMap<K, V, C, S> {}
+
+impl<K: PartialOrd, V: PartialOrd, C, S: Storage<(K, V)>> PartialOrd for FlatMap<K, V, C, S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.items.as_slice().partial_cmp(other.items.as_slice())
+    }
+}
+
+impl<K: Ord, V: Ord, C, S: Storage<(K, V)>> Ord for FlatMap<K, V, C, S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.items.as_slice().cmp(other.items.as_slice())
+    }
+}
+
+impl<K: Hash, V: Hash, C, S: Storage<(K, V)>> Hash for FlatMap<K, V, C, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.items.as_slice().hash(state);
+    }
+}
+
+impl<K, V, C: Default, S: Default> Default for FlatMap<K, V, C, S> {
+    fn default() -> Self {
+        Self::from_parts(S::default(), C::default())
+    }
+}
+
+impl<K, V, C, S> FlatMap<K, V, C, S> {
+    pub(crate) const fn from_parts(items: S, cmp: C) -> Self {
+        Self { items, cmp, marker: PhantomData }
+    }
+
+    pub(crate) fn into_parts(self) -> (S, C) {
+        (self.items, self.cmp)
     }
 }
 
 impl<K: Ord, V> FlatMap<K, V> {
     pub fn new() -> Self {
-        Self { items: Vec::new() }
+        Self::with_comparator(NaturalOrder)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_comparator(capacity, NaturalOrder)
+    }
+
+    /// Builds a map from unsorted input, resolving duplicate keys according to `policy`.
+    pub fn from_vec_with_policy(
+        mut items: Vec<(K, V)>,
+        policy: DuplicatePolicy<V>,
+    ) -> Result<Self, DuplicateKeyError<K>> {
+        items.sort_by(|a, b| K::cmp(&a.0, &b.0));
+
+        let mut deduped: Vec<(K, V)> = Vec::with_capacity(items.len());
+        for (key, value) in items {
+            match deduped.last_mut() {
+                Some(last) if last.0 == key => match policy {
+                    DuplicatePolicy::KeepFirst => {}
+                    DuplicatePolicy::KeepLast => *last = (key, value),
+                    DuplicatePolicy::Error => return Err(DuplicateKeyError { key }),
+                    DuplicatePolicy::Merge(merge) => {
+                        let (last_key, last_value) = deduped.pop().unwrap();
+                        deduped.push((last_key, merge(last_value, value)));
+                    }
+                },
+                _ => deduped.push((key, value)),
+            }
+        }
+
+        let map = Self::from_parts(deduped, NaturalOrder);
+        map.debug_check_invariants();
+        Ok(map)
+    }
+
+    /// Wraps a vector that is already sorted by key and free of duplicates, without checking it.
+    ///
+    /// Lookups on a map built from unsorted input return unspecified results.
+    /// The order is verified with the `debug-invariants` feature.
+    pub fn from_sorted_vec_unchecked(items: Vec<(K, V)>) -> Self {
+        let map = Self::from_parts(items, NaturalOrder);
+        map.debug_check_invariants();
+        map
+    }
+
+    /// Wraps a vector that is already sorted by key and free of duplicates in O(n).
+    pub fn try_from_sorted(items: Vec<(K, V)>) -> Result<Self, NotSortedError> {
+        Self::try_from_sorted_storage(items, NaturalOrder)
+    }
+
+    /// Builds a map with exactly the keys of `keys` in O(n), computing each value with `f`.
+    pub fn from_keys_with(keys: &FlatSet<K>, mut f: impl FnMut(&K) -> V) -> Self
+    where
+        K: Clone,
+    {
+        let items = keys.iter().map(|k| (k.clone(), f(k))).collect();
+        Self::from_parts(items, NaturalOrder)
+    }
+
+    /// Returns a set of the keys in O(n), without re-sorting.
+    pub fn keys_to_set(&self) -> FlatSet<K>
+    where
+        K: Clone,
+    {
+        FlatSet::from_sorted_vec_unchecked(self.keys().cloned().collect())
+    }
+
+    /// Turns the map into a set of its keys in O(n), without re-sorting.
+    pub fn into_keys_set(self) -> FlatSet<K> {
+        FlatSet::from_sorted_vec_unchecked(self.items.into_iter().map(|(k, _)| k).collect())
+    }
+}
+
+impl<K, V, C: Compare<K>, S: Storage<(K, V)>> FlatMap<K, V, C, S> {
+    /// Wraps storage that is already sorted by `cmp` and free of duplicates in O(n).
+    pub fn try_from_sorted_storage(items: S, cmp: C) -> Result<Self, NotSortedError> {
+        match sorted::first_unsorted(items.as_slice(), &cmp) {
+            Some(index) => Err(NotSortedError { index }),
+            None => Ok(Self::from_parts(items, cmp)),
+        }
+    }
+
+    pub fn comparator(&self) -> &C {
+        &self.cmp
     }
 
     // lookup
 
-    pub fn contains_key(&self, key: &K) -> bool {
-        self.items
-            .binary_search_by(|probe| K::cmp(&probe.0, key))
-            .is_ok()
+    pub fn contains_key<Q: ?Sized>(&self, key: &Q) -> bool
+    where
+        C: Compare<K, Q>,
+    {
+        self.search(key).is_ok()
     }
 
-    pub fn get(&self, key: &K) -> Option<&V> {
-        self.items
-            .binary_search_by(|probe| K::cmp(&probe.0, key))
+    pub fn get<Q: ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        C: Compare<K, Q>,
+    {
+        self.search(key)
             .ok()
-            .map(|i| &self.items[i].1)
+            .map(|i| &self.items.as_slice()[i].1)
     }
 
-    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
-        self.items
-            .binary_search_by(|probe| K::cmp(&probe.0, key))
+    pub fn get_mut<Q: ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        C: Compare<K, Q>,
+    {
+        self.search(key)
             .ok()
-            .map(|i| &mut self.items[i].1)
+            .map(|i| &mut self.items.as_mut_slice()[i].1)
     }
 
-    pub fn get_key_value(&self, key: &K) -> Option<(&K, &V)> {
+    /// Returns mutable references to the values of `N` distinct keys at once.
+    ///
+    /// Returns `None` if any key is missing or the same key is requested more than once.
+    pub fn get_many_mut<Q: ?Sized, const N: usize>(&mut self, keys: [&Q; N]) -> Option<[&mut V; N]>
+    where
+        C: Compare<K, Q>,
+    {
+        let mut indices = [0; N];
+        for (index, key) in indices.iter_mut().zip(keys) {
+            *index = self.search(key).ok()?;
+        }
+
         self.items
-            .binary_search_by(|probe| K::cmp(&probe.0, key))
+            .as_mut_slice()
+            .get_disjoint_mut(indices)
+            .ok()
+            .map(|entries| entries.map(|(_, v)| v))
+    }
+
+    /// Looks up many keys at once, returning the values in the order of `keys`.
+    ///
+    /// The keys are sorted and answered with a single galloping pass over the map,
+    /// which beats a binary search per key for large batches.
+    pub fn get_batch<Q>(&self, keys: &[Q]) -> Vec<Option<&V>>
+    where
+        C: Compare<K, Q> + Compare<Q>,
+    {
+        sorted::get_batch(self.items.as_slice(), keys, &self.cmp)
+    }
+
+    /// Like [`FlatMap::get_batch`], but skips sorting for `keys` that are already in map order.
+    ///
+    /// Results for keys that are out of order are unspecified.
+    pub fn get_sorted_batch<Q>(&self, keys: &[Q]) -> Vec<Option<&V>>
+    where
+        C: Compare<K, Q>,
+    {
+        sorted::get_sorted_batch(self.items.as_slice(), keys, &self.cmp)
+    }
+
+    pub fn get_key_value<Q: ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        C: Compare<K, Q>,
+    {
+        self.search(key)
             .ok()
             .map(|i| {
-                let (k, v) = &self.items[i];
+                let (k, v) = &self.items.as_slice()[i];
                 (k, v)
             })
     }
 
-    pub fn range(&self, range: impl RangeBounds<K>) -> impl Iterator<Item=(&K, &V)> {
-        let start_pos = match range.start_bound() {
-            Bound::Included(key) => self
-                .items
-                .binary_search_by(|probe| K::cmp(&probe.0, key))
-                .unwrap_or_else(|i| i),
-            Bound::Excluded(key) => self
-                .items
-                .binary_search_by(|probe| K::cmp(&probe.0, key))
-                .unwrap_or_else(|i| i + 1),
-            Bound::Unbounded => 0,
-        };
+    pub fn get_key_value_mut<Q: ?Sized>(&mut self, key: &Q) -> Option<(&K, &mut V)>
+    where
+        C: Compare<K, Q>,
+    {
+        self.search(key)
+            .ok()
+            .map(|i| {
+                let (k, v) = &mut self.items.as_mut_slice()[i];
+                (&*k, v)
+            })
+    }
 
-        let end_pos = match range.end_bound() {
-            Bound::Included(key) => self
-                .items
-                .binary_search_by(|probe| K::cmp(&probe.0, key))
-                .unwrap_or_else(|i| i + 1),
-            Bound::Excluded(key) => self
-                .items
-                .binary_search_by(|probe| K::cmp(&probe.0, key))
-                .unwrap_or_else(|i| i),
-            Bound::Unbounded => self.items.len(),
-        };
+    /// Returns the entry with the greatest key less than or equal to `key`.
+    pub fn get_floor<Q: ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        C: Compare<K, Q>,
+    {
+        self.upper_bound(key).checked_sub(1).and_then(|i| self.get_index(i))
+    }
+
+    /// Returns the entry with the least key greater than or equal to `key`.
+    pub fn get_ceiling<Q: ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        C: Compare<K, Q>,
+    {
+        self.get_index(self.lower_bound(key))
+    }
+
+    /// Returns the entry with the least key strictly greater than `key`.
+    pub fn get_next<Q: ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        C: Compare<K, Q>,
+    {
+        self.get_index(self.upper_bound(key))
+    }
+
+    /// Returns the entry with the greatest key strictly less than `key`.
+    pub fn get_prev<Q: ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        C: Compare<K, Q>,
+    {
+        self.lower_bound(key).checked_sub(1).and_then(|i| self.get_index(i))
+    }
+
+    // positional access
+
+    /// Returns the entry at position `index` in key order.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.items.as_slice().get(index).map(|(k, v)| (k, v))
+    }
 
-        self.items[start_pos..end_pos].iter().map(|(k, v)| (k, v))
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.items.as_mut_slice().get_mut(index).map(|(k, v)| (&*k, v))
     }
 
+    /// Returns the position of `key` in key order.
+    pub fn index_of<Q: ?Sized>(&self, key: &Q) -> Option<usize>
+    where
+        C: Compare<K, Q>,
+    {
+        self.search(key).ok()
+    }
+
+    /// Returns the number of keys strictly less than `key`.
+    pub fn rank<Q: ?Sized>(&self, key: &Q) -> usize
+    where
+        C: Compare<K, Q>,
+    {
+        self.lower_bound(key)
+    }
+
+    /// Returns the position of the first key that is greater than or equal to `key`.
+    pub fn lower_bound<Q: ?Sized>(&self, key: &Q) -> usize
+    where
+        C: Compare<K, Q>,
+    {
+        sorted::lower_bound(self.items.as_slice(), key, &self.cmp)
+    }
+
+    /// Returns the position of the first key that is greater than `key`.
+    pub fn upper_bound<Q: ?Sized>(&self, key: &Q) -> usize
+    where
+        C: Compare<K, Q>,
+    {
+        sorted::upper_bound(self.items.as_slice(), key, &self.cmp)
+    }
+
+    // ranges
+
+    pub fn range<Q: ?Sized>(&self, range: impl RangeBounds<Q>) -> Iter<'_, K, V>
+    where
+        C: Compare<K, Q>,
+    {
+        let range = self.range_indices(range);
+        Iter { inner: self.items.as_slice()[range].iter() }
+    }
+
+    pub fn range_keys<Q: ?Sized>(&self, range: impl RangeBounds<Q>) -> Keys<'_, K, V>
+    where
+        C: Compare<K, Q>,
+    {
+        let range = self.range_indices(range);
+        Keys { inner: self.items.as_slice()[range].iter() }
+    }
+
+    pub fn range_values<Q: ?Sized>(&self, range: impl RangeBounds<Q>) -> Values<'_, K, V>
+    where
+        C: Compare<K, Q>,
+    {
+        let range = self.range_indices(range);
+        Values { inner: self.items.as_slice()[range].iter() }
+    }
+
+    /// Counts the entries within `range` with two binary searches.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of `range` is greater than its end, like [`FlatMap::range`].
+    pub fn range_len<Q: ?Sized>(&self, range: impl RangeBounds<Q>) -> usize
+    where
+        C: Compare<K, Q>,
+    {
+        let range = self.range_indices(range);
+        assert!(range.start <= range.end, "range start is greater than range end in FlatMap");
+        range.len()
+    }
+
+    pub fn range_mut<Q: ?Sized>(&mut self, range: impl RangeBounds<Q>) -> IterMut<'_, K, V>
+    where
+        C: Compare<K, Q>,
+    {
+        let range = self.range_indices(range);
+        IterMut { inner: self.items.as_mut_slice()[range].iter_mut() }
+    }
+
+    // misc
+
+    pub fn is_empty(&self) -> bool {
+        self.items.as_slice().is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.as_slice().len()
+    }
+
+    /// Verifies that the keys are strictly increasing, i.e. sorted and free of duplicates.
+    ///
+    /// With the `debug-invariants` feature this runs automatically after operations that
+    /// add or rearrange entries, panicking on violation.
+    pub fn check_invariants(&self) -> Result<(), NotSortedError> {
+        match sorted::first_unsorted(self.items.as_slice(), &self.cmp) {
+            Some(index) => Err(NotSortedError { index }),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the entries as a slice sorted by key.
+    pub fn as_slice(&self) -> &[(K, V)] {
+        self.items.as_slice()
+    }
+
+    /// Returns the underlying storage, sorted by key.
+    pub fn into_storage(self) -> S {
+        self.items
+    }
+
+    // iterators
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter { inner: self.items.as_slice().iter() }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut { inner: self.items.as_mut_slice().iter_mut() }
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.items.as_slice().iter() }
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.items.as_slice().iter() }
+    }
+
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut { inner: self.items.as_mut_slice().iter_mut() }
+    }
+
+    /// Iterates from the first key greater than or equal to `key` to the end of the map.
+    pub fn iter_from<Q: ?Sized>(&self, key: &Q) -> Iter<'_, K, V>
+    where
+        C: Compare<K, Q>,
+    {
+        let start = self.lower_bound(key);
+        Iter { inner: self.items.as_slice()[start..].iter() }
+    }
+
+    pub fn iter_from_mut<Q: ?Sized>(&mut self, key: &Q) -> IterMut<'_, K, V>
+    where
+        C: Compare<K, Q>,
+    {
+        let start = self.lower_bound(key);
+        IterMut { inner: self.items.as_mut_slice()[start..].iter_mut() }
+    }
+
+    // cursors
+
+    pub fn cursor_front(&self) -> Cursor<'_, K, V> {
+        Cursor { items: self.items.as_slice(), index: 0 }
+    }
+
+    pub fn cursor_back(&self) -> Cursor<'_, K, V> {
+        Cursor { items: self.items.as_slice(), index: self.len() }
+    }
+
+    /// Returns a cursor positioned right before the first key greater than or equal to `key`.
+    pub fn cursor_at<Q: ?Sized>(&self, key: &Q) -> Cursor<'_, K, V>
+    where
+        C: Compare<K, Q>,
+    {
+        let index = self.search(key).unwrap_or_else(|i| i);
+        Cursor { items: self.items.as_slice(), index }
+    }
+
+    // internals
+
+    fn search<Q: ?Sized>(&self, key: &Q) -> Result<usize, usize>
+    where
+        C: Compare<K, Q>,
+    {
+        sorted::search(self.items.as_slice(), key, &self.cmp)
+    }
+
+    pub(crate) fn debug_check_invariants(&self) {
+        debug_check_order(self.items.as_slice(), &self.cmp);
+    }
+
+    fn range_indices<Q: ?Sized>(&self, range: impl RangeBounds<Q>) -> Range<usize>
+    where
+        C: Compare<K, Q>,
+    {
+        sorted::range_indices(self.items.as_slice(), range, &self.cmp)
+    }
+}
+
+impl<K, V, C: Compare<K>, S: StorageMut<(K, V)>> FlatMap<K, V, C, S> {
     // modification
 
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
-        if let Some((last_key, last_value)) = &mut self.items.last_mut() {
-            match K::cmp(last_key, &key) {
+        if let Some((last_key, last_value)) = self.items.as_mut_slice().last_mut() {
+            match self.cmp.compare(last_key, &key) {
                 Ordering::Less => {
                     self.items.push((key, value));
+                    self.debug_check_invariants();
                     return None;
                 }
                 Ordering::Equal => {
@@ -124,72 +650,401 @@ impl<K: Ord, V> FlatMap<K, V> {
             }
         }
 
-        match self.items.binary_search_by(|probe| K::cmp(&probe.0, &key)) {
-            Ok(i) => self.items[i].1 = value,
-            Err(i) => self.items.insert(i, (key, value)),
+        match self.search(&key) {
+            Ok(i) => Some(mem::replace(&mut self.items.as_mut_slice()[i].1, value)),
+            Err(i) => {
+                self.items.insert(i, (key, value));
+                self.debug_check_invariants();
+                None
+            }
+        }
+    }
+
+    /// Applies `f` to the value of `key` if present, returning whether it was.
+    pub fn update<Q: ?Sized>(&mut self, key: &Q, f: impl FnOnce(&mut V)) -> bool
+    where
+        C: Compare<K, Q>,
+    {
+        match self.get_mut(key) {
+            Some(value) => {
+                f(value);
+                true
+            }
+            None => false,
         }
+    }
+
+    /// Returns the value of `key`, inserting the result of `default` first if it is missing.
+    pub fn get_mut_or_insert_with(&mut self, key: K, default: impl FnOnce() -> V) -> &mut V {
+        let i = match self.search(&key) {
+            Ok(i) => i,
+            Err(i) => {
+                self.items.insert(i, (key, default()));
+                self.debug_check_invariants();
+                i
+            }
+        };
+        &mut self.items.as_mut_slice()[i].1
+    }
 
-        None
+    pub fn remove<Q: ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        C: Compare<K, Q>,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
     }
 
-    pub fn remove(&mut self, key: &K) -> Option<V> {
-        if let Some((last_key, _)) = &self.items.last() {
-            match K::cmp(last_key, key) {
+    pub fn remove_entry<Q: ?Sized>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        C: Compare<K, Q>,
+    {
+        if let Some((last_key, _)) = self.items.as_slice().last() {
+            match self.cmp.compare(last_key, key) {
                 Ordering::Less => return None,
-                Ordering::Equal => {
-                    return self.items.pop().map(|(_, v)| v);
-                }
+                Ordering::Equal => return self.items.pop(),
                 Ordering::Greater => {}
             }
         }
 
-        self.items
-            .binary_search_by(|probe| K::cmp(&probe.0, key))
+        self.search(key)
             .ok()
-            .map(|i| self.items.remove(i).1)
+            .map(|i| self.items.remove(i))
     }
 
-    // misc
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, f: F) {
+        self.retain_mut(f);
+    }
 
-    pub fn is_empty(&self) -> bool {
-        self.items.is_empty()
+    /// Same as [`FlatMap::retain`], named after [`Vec::retain_mut`].
+    pub fn retain_mut<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
+        self.items.retain_mut(|(k, v)| f(k, v));
     }
 
+    // misc
 
     pub fn clear(&mut self) {
         self.items.clear();
     }
 
-    pub fn len(&self) -> usize {
-        self.items.len()
+    pub fn capacity(&self) -> usize {
+        self.items.capacity()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.items.reserve(additional);
+    }
+}
+
+impl<K, V, C: Compare<K>> FlatMap<K, V, C> {
+    pub fn with_comparator(cmp: C) -> Self {
+        Self::from_parts(Vec::new(), cmp)
+    }
+
+    pub fn with_capacity_and_comparator(capacity: usize, cmp: C) -> Self {
+        Self::from_parts(Vec::with_capacity(capacity), cmp)
+    }
+
+    /// Builds a map ordered by `cmp` from unsorted input. If there are duplicates, the last one is kept.
+    pub fn from_vec_with_comparator(mut items: Vec<(K, V)>, cmp: C) -> Self {
+        sorted::sort_dedup(&mut items, &cmp);
+        Self::from_parts(items, cmp)
+    }
+
+    /// Converts the map into an immutable [`FrozenFlatMap`] without re-sorting.
+    pub fn freeze(self) -> FrozenFlatMap<K, V, C>
+    where
+        K: Ord,
+    {
+        FrozenFlatMap::from(self)
+    }
+
+    // modification
+
+    /// Inserts a batch of entries by sorting it and merging it with the existing entries
+    /// in a single pass, instead of shifting the tail once per key.
+    ///
+    /// Later entries of the batch win over earlier ones and over existing entries.
+    pub fn insert_many(&mut self, iter: impl IntoIterator<Item=(K, V)>) {
+        let mut other = iter.into_iter().collect();
+        sorted::sort_dedup(&mut other, &self.cmp);
+        let items = mem::take(&mut self.items);
+        self.items = sorted::merge(items, other, &self.cmp, |_, _, v| v);
+        self.debug_check_invariants();
+    }
+
+    /// Removes all entries with the given keys in a single compaction pass, in O(n + m log m).
+    ///
+    /// Returns the removed values in key order. Missing and repeated keys are ignored.
+    pub fn remove_many(&mut self, keys: impl IntoIterator<Item=K>) -> Vec<V> {
+        let mut keys = keys.into_iter().collect::<Vec<_>>();
+        keys.sort_by(|a, b| self.cmp.compare(a, b));
+
+        let cmp = &self.cmp;
+        let mut pending = keys.iter().peekable();
+        self.items
+            .extract_if(.., |(k, _)| {
+                while pending.next_if(|q| cmp.compare(k, q) == Ordering::Greater).is_some() {}
+                pending.peek().is_some_and(|q| cmp.compare(k, q) == Ordering::Equal)
+            })
+            .map(|(_, v)| v)
+            .collect()
+    }
+
+    /// Moves all entries from `other` into `self`, leaving `other` empty.
+    ///
+    /// Values from `other` win on equal keys. Runs in O(n + m).
+    pub fn append(&mut self, other: &mut Self) {
+        let other = mem::take(&mut other.items);
+        let items = mem::take(&mut self.items);
+        self.items = sorted::merge(items, other, &self.cmp, |_, _, v| v);
+        self.debug_check_invariants();
+    }
+
+    /// Removes all entries within `range` with a single shift of the tail, yielding them in key order.
+    ///
+    /// The entries are removed even if the returned iterator is not consumed.
+    pub fn remove_range<Q: ?Sized>(&mut self, range: impl RangeBounds<Q>) -> vec::Drain<'_, (K, V)>
+    where
+        C: Compare<K, Q>,
+    {
+        let range = self.range_indices(range);
+        self.items.drain(range)
+    }
+
+    /// Merges two maps in O(n + m), combining the values of keys present in both with `resolve`.
+    pub fn merge_with<F>(self, other: Self, resolve: F) -> Self
+    where
+        F: FnMut(&K, V, V) -> V,
+    {
+        let items = sorted::merge(self.items, other.items, &self.cmp, resolve);
+        let merged = Self::from_parts(items, self.cmp);
+        merged.debug_check_invariants();
+        merged
+    }
+
+    /// Joins two maps where every key of `lower` is less than every key of `upper`
+    /// by appending the storage, without merging.
+    pub fn concat(mut lower: Self, mut upper: Self) -> Result<Self, OverlapError<Self>> {
+        if let (Some((last, _)), Some((first, _))) = (lower.items.last(), upper.items.first()) {
+            if lower.cmp.compare(last, first) != Ordering::Less {
+                return Err(OverlapError { lower, upper });
+            }
+        }
+
+        lower.items.append(&mut upper.items);
+        lower.debug_check_invariants();
+        Ok(lower)
+    }
+
+    /// Splits the map into the entries matching `pred` and the rest, preserving order.
+    pub fn partition(self, mut pred: impl FnMut(&K, &V) -> bool) -> (Self, Self)
+    where
+        C: Clone,
+    {
+        let (matching, rest) = self.items
+                                   .into_iter()
+                                   .partition(|(k, v)| pred(k, v));
+        (Self::from_parts(matching, self.cmp.clone()), Self::from_parts(rest, self.cmp))
+    }
+
+    /// Splits the map in two at `key`, returning everything greater than or equal to it.
+    pub fn split_off<Q: ?Sized>(&mut self, key: &Q) -> Self
+    where
+        C: Compare<K, Q> + Clone,
+    {
+        let at = self.search(key).unwrap_or_else(|i| i);
+        Self::from_parts(self.items.split_off(at), self.cmp.clone())
+    }
+
+    /// Removes and returns all entries with keys less than `key`.
+    pub fn split_before<Q: ?Sized>(&mut self, key: &Q) -> Self
+    where
+        C: Compare<K, Q> + Clone,
+    {
+        let at = self.lower_bound(key);
+        let tail = self.items.split_off(at);
+        Self::from_parts(mem::replace(&mut self.items, tail), self.cmp.clone())
+    }
+
+    /// Removes and returns all entries with keys greater than `key`.
+    pub fn split_after<Q: ?Sized>(&mut self, key: &Q) -> Self
+    where
+        C: Compare<K, Q> + Clone,
+    {
+        let at = self.upper_bound(key);
+        Self::from_parts(self.items.split_off(at), self.cmp.clone())
+    }
+
+    // misc
+
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.items.reserve_exact(additional);
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.items.shrink_to_fit();
+    }
+
+    /// Transforms every value while keeping the keys and their order, without re-sorting.
+    pub fn map_values<U>(self, mut f: impl FnMut(&K, V) -> U) -> FlatMap<K, U, C> {
+        let items = self.items
+                        .into_iter()
+                        .map(|(k, v)| {
+                            let u = f(&k, v);
+                            (k, u)
+                        })
+                        .collect();
+        FlatMap::from_parts(items, self.cmp)
+    }
+
+    /// Returns the underlying vector, sorted by key.
+    pub fn into_inner(self) -> Vec<(K, V)> {
+        self.items
     }
 
     // iterators
 
-    pub fn iter(&self) -> impl Iterator<Item=(&K, &V)> {
-        self.items.iter().map(|(k, v)| (k, v))
+    /// Removes all entries in key order, keeping the allocated memory for reuse.
+    pub fn drain(&mut self) -> vec::Drain<'_, (K, V)> {
+        self.items.drain(..)
+    }
+
+    /// Lazily removes and yields the entries for which `pred` returns `true`.
+    ///
+    /// Entries the iterator doesn't get to are kept if it is dropped early.
+    pub fn extract_if<'a, F>(&'a mut self, mut pred: F) -> impl Iterator<Item=(K, V)> + 'a
+    where
+        F: FnMut(&K, &mut V) -> bool + 'a,
+    {
+        self.items.extract_if(.., move |(k, v)| pred(k, v))
     }
 
-    pub fn iter_mut(&mut self) -> impl Iterator<Item=(&K, &mut V)> {
-        self.items.iter_mut().map(|(k, v)| -> (&K, &mut V){ (k, v) })
+    // entries
+
+    pub fn first_entry(&mut self) -> Option<OccupiedEntry<'_, K, V>> {
+        if self.items.is_empty() {
+            return None;
+        }
+        Some(OccupiedEntry { items: &mut self.items, index: 0 })
     }
 
-    pub fn keys(&self) -> impl Iterator<Item=&K> {
-        self.items.iter().map(|(k, _)| k)
+    pub fn last_entry(&mut self) -> Option<OccupiedEntry<'_, K, V>> {
+        let index = self.items.len().checked_sub(1)?;
+        Some(OccupiedEntry { items: &mut self.items, index })
     }
 
-    pub fn values(&self) -> impl Iterator<Item=&V> {
-        self.items.iter().map(|(_, v)| v)
+    // cursors
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, K, V, C> {
+        CursorMut { items: &mut self.items, cmp: &self.cmp, index: 0 }
     }
 
-    pub fn values_mut(&mut self) -> impl Iterator<Item=&mut V> {
-        self.items.iter_mut().map(|(_, v)| v)
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, K, V, C> {
+        let index = self.items.len();
+        CursorMut { items: &mut self.items, cmp: &self.cmp, index }
+    }
+
+    /// Returns a mutable cursor positioned right before the first key greater than or equal to `key`.
+    pub fn cursor_at_mut<Q: ?Sized>(&mut self, key: &Q) -> CursorMut<'_, K, V, C>
+    where
+        C: Compare<K, Q>,
+    {
+        let index = self.search(key).unwrap_or_else(|i| i);
+        CursorMut { items: &mut self.items, cmp: &self.cmp, index }
+    }
+}
+
+/// Panics if `items` are not strictly increasing by `cmp`, with the `debug-invariants` feature.
+fn debug_check_order<K, V, C: Compare<K>>(items: &[(K, V)], cmp: &C) {
+    if cfg!(feature = "debug-invariants") {
+        if let Some(index) = sorted::first_unsorted(items, cmp) {
+            panic!("FlatMap invariants violated: {}", NotSortedError { index });
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Branchless, Interpolation, OutOfOrderError, Reversed, UnorderedKeyError};
+    use std::ops::Bound;
+
+    #[test]
+    fn test_traits() {
+        let a = FlatMap::from([(1, 2), (3, 4)]);
+        let b = a.clone();
+        assert_eq!(a, b);
+        assert_eq!(format!("{a:?}"), "{1: 2, 3: 4}");
+        assert!(a < FlatMap::from([(1, 3)]));
+        assert!(FlatMap::<i32, i32>::default().is_empty());
+
+        let mut outer = FlatMap::new();
+        outer.insert(a, "a");
+        assert_eq!(outer.get(&b), Some(&"a"));
+    }
+
+    #[test]
+    fn test_from_vec_with_policy() {
+        let input = || vec![(3, 1), (1, 2), (3, 3), (3, 4)];
+
+        let m = FlatMap::from_vec_with_policy(input(), DuplicatePolicy::KeepFirst).unwrap();
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&1, &2), (&3, &1)]);
+        let m = FlatMap::from_vec_with_policy(input(), DuplicatePolicy::KeepLast).unwrap();
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&1, &2), (&3, &4)]);
+        let m = FlatMap::from_vec_with_policy(input(), DuplicatePolicy::Merge(|a, b| a + b)).unwrap();
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&1, &2), (&3, &8)]);
+        let err = FlatMap::from_vec_with_policy(input(), DuplicatePolicy::Error).unwrap_err();
+        assert_eq!(err.key(), &3);
+        assert!(FlatMap::from_vec_with_policy(vec![(1, 2), (3, 4)], DuplicatePolicy::Error).is_ok());
+    }
+
+    #[test]
+    fn test_builder() {
+        let mut b = FlatMapBuilder::new();
+        assert_eq!(b.push(1, 2), Ok(()));
+        assert_eq!(b.push(3, 4), Ok(()));
+        assert_eq!(b.push(3, 5), Err(OutOfOrderError));
+        assert_eq!(b.push(2, 5), Err(OutOfOrderError));
+        assert_eq!(b.len(), 2);
+        assert_eq!(b.finish().iter().collect::<Vec<_>>(), vec![(&1, &2), (&3, &4)]);
+
+        let mut b = FlatMapBuilder::with_capacity(1);
+        b.push("a", 1).unwrap();
+        assert_eq!(b.finish_frozen().get("a"), Some(&1));
+    }
+
+    #[test]
+    fn test_from_sorted() {
+        let m = FlatMap::from_sorted_vec_unchecked(vec![(1, 2), (3, 4)]);
+        assert_eq!(m.get(&3), Some(&4));
+
+        let m = FlatMap::try_from_sorted(vec![(1, 2), (3, 4), (5, 6)]).unwrap();
+        assert_eq!(m.len(), 3);
+        let err = FlatMap::try_from_sorted(vec![(1, 2), (5, 6), (3, 4)]).unwrap_err();
+        assert_eq!(err.index(), 2);
+        let err = FlatMap::try_from_sorted(vec![(1, 2), (1, 3)]).unwrap_err();
+        assert_eq!(err.index(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "FlatMap invariants violated")]
+    #[cfg(feature = "debug-invariants")]
+    fn test_from_sorted_vec_unchecked_debug_invariants() {
+        FlatMap::from_sorted_vec_unchecked(vec![(3, 4), (1, 2)]);
+    }
+
+    #[test]
+    fn test_std_conversions() {
+        let b = BTreeMap::from([(3, 4), (1, 2)]);
+        let m = FlatMap::from(b.clone());
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&1, &2), (&3, &4)]);
+        assert_eq!(BTreeMap::from(m), b);
+
+        let h = HashMap::from([(5, 6), (3, 4), (1, 2)]);
+        let m = FlatMap::from(h.clone());
+        assert_eq!(m.keys().collect::<Vec<_>>(), vec![&1, &3, &5]);
+        assert_eq!(HashMap::from(m), h);
+    }
 
     #[test]
     fn test_contains_key() {
@@ -218,6 +1073,33 @@ mod tests {
         assert_eq!(m.get(&3), Some(&22));
     }
 
+    #[test]
+    fn test_get_many_mut() {
+        let mut m = FlatMap::from([(1, 10), (3, 40), (5, 60)]);
+        let [a, b] = m.get_many_mut([&1, &5]).unwrap();
+        mem::swap(a, b);
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&1, &60), (&3, &40), (&5, &10)]);
+        assert!(m.get_many_mut([&1, &1]).is_none());
+        assert!(m.get_many_mut([&1, &2]).is_none());
+    }
+
+    #[test]
+    fn test_get_batch() {
+        let m = (0..100).map(|i| (i * 2, i)).collect::<FlatMap<_, _>>();
+        assert_eq!(
+            m.get_batch(&[150, 3, 0, 198, 150, 200, -1]),
+            vec![Some(&75), None, Some(&0), Some(&99), Some(&75), None, None]
+        );
+        assert!(m.get_batch::<i32>(&[]).is_empty());
+        assert_eq!(FlatMap::<i32, i32>::new().get_batch(&[1]), vec![None]);
+    }
+
+    #[test]
+    fn test_get_sorted_batch() {
+        let m = (0..100).map(|i| (i * 2, i)).collect::<FlatMap<_, _>>();
+        assert_eq!(m.get_sorted_batch(&[-1, 0, 3, 150, 150, 198, 200]), vec![None, Some(&0), None, Some(&75), Some(&75), Some(&99), None]);
+    }
+
     #[test]
     fn test_get_key_value() {
         let m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
@@ -228,10 +1110,280 @@ mod tests {
         assert_eq!(m.get_key_value(&100), None);
     }
 
+    #[test]
+    fn test_borrowed_lookup() {
+        let mut m = FlatMap::from([("a".to_string(), 1), ("b".to_string(), 2)]);
+        assert!(m.contains_key("a"));
+        assert_eq!(m.get("b"), Some(&2));
+        *m.get_mut("a").unwrap() = 11;
+        assert_eq!(m.get_key_value("a"), Some((&"a".to_string(), &11)));
+        assert_eq!(m.remove("b"), Some(2));
+        assert_eq!(m.get("c"), None);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let m = FlatMap::from([(10, 'a'), (20, 'b'), (30, 'c')]);
+        assert_eq!(m.get_floor(&25), Some((&20, &'b')));
+        assert_eq!(m.get_floor(&20), Some((&20, &'b')));
+        assert_eq!(m.get_floor(&5), None);
+        assert_eq!(m.get_ceiling(&25), Some((&30, &'c')));
+        assert_eq!(m.get_ceiling(&20), Some((&20, &'b')));
+        assert_eq!(m.get_ceiling(&35), None);
+        assert_eq!(m.get_next(&20), Some((&30, &'c')));
+        assert_eq!(m.get_next(&30), None);
+        assert_eq!(m.get_prev(&20), Some((&10, &'a')));
+        assert_eq!(m.get_prev(&10), None);
+    }
+
+    #[test]
+    fn test_get_index() {
+        let mut m = FlatMap::from([(5, 6), (1, 2), (3, 4)]);
+        assert_eq!(m.get_index(0), Some((&1, &2)));
+        assert_eq!(m.get_index(2), Some((&5, &6)));
+        assert_eq!(m.get_index(3), None);
+        *m.get_index_mut(1).unwrap().1 = 44;
+        assert_eq!(m.get(&3), Some(&44));
+        assert_eq!(m.index_of(&5), Some(2));
+        assert_eq!(m.index_of(&4), None);
+    }
+
+    #[test]
+    fn test_rank() {
+        let m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
+        assert_eq!(m.rank(&0), 0);
+        assert_eq!(m.rank(&1), 0);
+        assert_eq!(m.rank(&2), 1);
+        assert_eq!(m.rank(&5), 2);
+        assert_eq!(m.rank(&6), 3);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
+        assert_eq!(m.lower_bound(&3), 1);
+        assert_eq!(m.upper_bound(&3), 2);
+        assert_eq!(m.lower_bound(&4), 2);
+        assert_eq!(m.upper_bound(&4), 2);
+        assert_eq!(m.lower_bound(&0), 0);
+        assert_eq!(m.upper_bound(&5), 3);
+    }
+
+    #[test]
+    fn test_index() {
+        let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
+        assert_eq!(m[&3], 4);
+        m[&3] = 44;
+        assert_eq!(m[&3], 44);
+    }
+
+    #[test]
+    #[should_panic(expected = "no entry found for key")]
+    fn test_index_missing() {
+        let m = FlatMap::from([(1, 2)]);
+        let _ = m[&3];
+    }
+
+    #[test]
+    #[cfg(feature = "equivalent")]
+    fn test_comparable_lookup() {
+        use equivalent::{Comparable, Equivalent};
+
+        struct Query<'a>(&'a str, u32);
+
+        impl Equivalent<(String, u32)> for Query<'_> {
+            fn equivalent(&self, key: &(String, u32)) -> bool {
+                self.0 == key.0 && self.1 == key.1
+            }
+        }
+
+        impl Comparable<(String, u32)> for Query<'_> {
+            fn compare(&self, key: &(String, u32)) -> Ordering {
+                (self.0, self.1).cmp(&(key.0.as_str(), key.1))
+            }
+        }
+
+        let mut m = FlatMap::from([(("a".to_string(), 1), 1), (("a".to_string(), 2), 2), (("b".to_string(), 1), 3)]);
+        assert_eq!(m.get(&Query("a", 2)), Some(&2));
+        assert_eq!(m.get(&Query("b", 2)), None);
+        assert_eq!(m.remove(&Query("b", 1)), Some(3));
+        assert_eq!(m.lower_bound(&Query("a", 3)), 2);
+    }
+
+    #[test]
+    fn test_reversed_comparator() {
+        let mut m = FlatMap::from_vec_with_comparator(vec![(1, 'a'), (3, 'c'), (2, 'b'), (3, 'd')], Reversed(NaturalOrder));
+        assert_eq!(m.keys().collect::<Vec<_>>(), vec![&3, &2, &1]);
+        assert_eq!(m.get(&3), Some(&'d'));
+        m.insert(0, 'z');
+        m.insert(4, 'e');
+        assert_eq!(m.keys().collect::<Vec<_>>(), vec![&4, &3, &2, &1, &0]);
+        assert_eq!(m.range((Bound::Included(3), Bound::Included(1))).count(), 3);
+        assert_eq!(m.lower_bound(&2), 2);
+        assert_eq!(m.remove(&0), Some('z'));
+        assert!(m.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn test_closure_comparator() {
+        let mut m = FlatMap::with_comparator(|a: &String, b: &String| {
+            a.to_lowercase().cmp(&b.to_lowercase())
+        });
+        m.insert("b".to_string(), 1);
+        m.insert("A".to_string(), 2);
+        m.insert("a".to_string(), 3);
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.get(&"B".to_string()), Some(&1));
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&"A".to_string(), &3), (&"b".to_string(), &1)]);
+    }
+
+    #[test]
+    fn test_search_strategies() {
+        let keys = (0..1000u64).map(|i| i * i % 7919).collect::<Vec<_>>();
+        let plain = keys.iter().map(|&k| (k, k)).collect::<FlatMap<_, _>>();
+        let branchless = keys.iter().map(|&k| (k, k)).collect::<FlatMap<_, _, Branchless>>();
+        let interpolation = keys.iter().map(|&k| (k, k)).collect::<FlatMap<_, _, Interpolation>>();
+        let descending = FlatMap::from_vec_with_comparator(
+            keys.iter().map(|&k| (k, k)).collect(),
+            Branchless(Reversed(NaturalOrder)),
+        );
+        for k in 0..8000 {
+            assert_eq!(branchless.get(&k), plain.get(&k));
+            assert_eq!(interpolation.get(&k), plain.get(&k));
+            assert_eq!(descending.get(&k), plain.get(&k));
+        }
+
+        let mut m = FlatMap::with_comparator(Interpolation::<NaturalOrder>::default());
+        for k in [-5i32, 100, 3, i32::MIN, i32::MAX, 0] {
+            m.insert(k, ());
+        }
+        assert_eq!(m.keys().copied().collect::<Vec<_>>(), vec![i32::MIN, -5, 0, 3, 100, i32::MAX]);
+        assert_eq!(m.index_of(&3), Some(3));
+        assert_eq!(m.remove(&-5), Some(()));
+        assert!(!m.contains_key(&-5));
+    }
+
+    #[test]
+    fn test_storage() {
+        let boxed = FlatMap::try_from_sorted_storage(vec![(1, 'a'), (2, 'b')].into_boxed_slice(), NaturalOrder).unwrap();
+        assert_eq!(boxed.get(&2), Some(&'b'));
+        assert_eq!(boxed.range(2..).count(), 1);
+        assert!(FlatMap::try_from_sorted_storage(vec![(2, 'b'), (1, 'a')].into_boxed_slice(), NaturalOrder).is_err());
+
+        #[cfg(feature = "smallvec")]
+        {
+            let mut m = FlatMap::<i32, i32, NaturalOrder, smallvec::SmallVec<[(i32, i32); 2]>>::default();
+            m.insert(3, 30);
+            m.insert(1, 10);
+            assert!(!m.into_storage().spilled());
+
+            let mut m = FlatMap::<i32, i32, NaturalOrder, smallvec::SmallVec<[(i32, i32); 2]>>::default();
+            for i in [5, 1, 3, 2, 4] {
+                m.insert(i, i * 10);
+            }
+            assert_eq!(m.remove(&3), Some(30));
+            assert_eq!(m.keys().collect::<Vec<_>>(), vec![&1, &2, &4, &5]);
+        }
+
+        #[cfg(feature = "arrayvec")]
+        {
+            let mut m = FlatMap::<i32, i32, NaturalOrder, arrayvec::ArrayVec<(i32, i32), 4>>::default();
+            for i in [4, 2, 3, 1] {
+                m.insert(i, i * 10);
+            }
+            assert_eq!(m.capacity(), 4);
+            assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&1, &10), (&2, &20), (&3, &30), (&4, &40)]);
+            m.retain(|k, _| k % 2 == 0);
+            assert_eq!(m.len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_remove_many() {
+        let mut m = (0..10).map(|i| (i, i * 10)).collect::<FlatMap<_, _>>();
+        assert_eq!(m.remove_many([7, 2, 42, 2, 0, 9]), vec![0, 20, 70, 90]);
+        assert_eq!(m.keys().copied().collect::<Vec<_>>(), vec![1, 3, 4, 5, 6, 8]);
+        assert!(m.remove_many([]).is_empty());
+        assert_eq!(m.len(), 6);
+    }
+
+    #[test]
+    fn test_get_key_value_mut() {
+        let mut m = FlatMap::from([(1, 2), (3, 4)]);
+        let (k, v) = m.get_key_value_mut(&3).unwrap();
+        assert_eq!(*k, 3);
+        *v = 5;
+        assert_eq!(m.get(&3), Some(&5));
+        assert_eq!(m.get_key_value_mut(&2), None);
+    }
+
+    #[test]
+    fn test_first_last_entry() {
+        let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
+        {
+            let mut first = m.first_entry().unwrap();
+            assert_eq!((first.index(), *first.key(), *first.get()), (0, 1, 2));
+            assert_eq!(first.insert(7), 2);
+            *first.get_mut() += 1;
+        }
+        assert_eq!(m.get(&1), Some(&8));
+        *m.last_entry().unwrap().into_mut() = 0;
+        assert_eq!(m.get(&5), Some(&0));
+        assert_eq!(m.last_entry().unwrap().remove_entry(), (5, 0));
+        assert_eq!(m.first_entry().unwrap().remove(), 8);
+        assert_eq!(m.keys().collect::<Vec<_>>(), vec![&3]);
+
+        let mut empty = FlatMap::<i32, i32>::new();
+        assert!(empty.first_entry().is_none());
+        assert!(empty.last_entry().is_none());
+    }
+
     #[test]
     fn test_range() {
         let m = FlatMap::from([(1, 2), (3, 4), (5, 6), (7, 8), (9, 10)]);
         assert_eq!(m.range(2..8).collect::<Vec<_>>(), vec![(&3, &4), (&5, &6), (&7, &8)]);
+        assert_eq!(m.range(3..=7).collect::<Vec<_>>(), vec![(&3, &4), (&5, &6), (&7, &8)]);
+        assert_eq!(m.range(4..=6).collect::<Vec<_>>(), vec![(&5, &6)]);
+        assert_eq!(
+            m.range((Bound::Excluded(3), Bound::Unbounded)).collect::<Vec<_>>(),
+            vec![(&5, &6), (&7, &8), (&9, &10)]
+        );
+        assert_eq!(
+            m.range((Bound::Excluded(4), Bound::Excluded(9))).collect::<Vec<_>>(),
+            vec![(&5, &6), (&7, &8)]
+        );
+    }
+
+    #[test]
+    fn test_range_keys_values() {
+        let m = FlatMap::from([(1, 2), (3, 4), (5, 6), (7, 8), (9, 10)]);
+        assert_eq!(m.range_keys(2..8).collect::<Vec<_>>(), vec![&3, &5, &7]);
+        assert_eq!(m.range_keys(2..8).rev().collect::<Vec<_>>(), vec![&7, &5, &3]);
+        assert_eq!(m.range_values(..=3).collect::<Vec<_>>(), vec![&2, &4]);
+        assert_eq!(m.range_values(4..).len(), 3);
+    }
+
+    #[test]
+    fn test_range_len() {
+        let m = FlatMap::from([(1, 2), (3, 4), (5, 6), (7, 8), (9, 10)]);
+        assert_eq!(m.range_len(2..8), 3);
+        assert_eq!(m.range_len(3..=3), 1);
+        assert_eq!(m.range_len(10..), 0);
+        assert_eq!(m.range_len::<i32>(..), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    fn test_range_len_reversed() {
+        let m = FlatMap::from([(1, 2), (3, 4)]);
+        m.range_len((Bound::Included(3), Bound::Excluded(1)));
+    }
+
+    #[test]
+    fn test_range_mut() {
+        let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6), (7, 8), (9, 10)]);
+        m.range_mut(3..=7).for_each(|(_, v)| *v = 0);
+        assert_eq!(m.values().collect::<Vec<_>>(), vec![&2, &0, &0, &0, &10]);
     }
 
     #[test]
@@ -241,6 +1393,36 @@ mod tests {
         assert_eq!(m.get(&7), Some(&8));
         assert_eq!(m.insert(7, 9), Some(8));
         assert_eq!(m.get(&7), Some(&9));
+        assert_eq!(m.insert(3, 44), Some(4));
+        assert_eq!(m.get(&3), Some(&44));
+        assert_eq!(m.insert(2, 3), None);
+        assert_eq!(m.keys().collect::<Vec<_>>(), vec![&1, &2, &3, &5, &7]);
+    }
+
+    #[test]
+    fn test_update() {
+        let mut m = FlatMap::from([(1, 2), (3, 4)]);
+        assert!(m.update(&3, |v| *v += 1));
+        assert!(!m.update(&5, |v| *v += 1));
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&1, &2), (&3, &5)]);
+    }
+
+    #[test]
+    fn test_get_mut_or_insert_with() {
+        let mut m = FlatMap::from([(1, 2), (3, 4)]);
+        *m.get_mut_or_insert_with(3, || 0) += 1;
+        *m.get_mut_or_insert_with(2, || 0) += 1;
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&1, &2), (&2, &1), (&3, &5)]);
+    }
+
+    #[test]
+    fn test_insert_many() {
+        let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
+        m.insert_many([(6, 7), (2, 3), (5, 66), (2, 33)]);
+        assert_eq!(
+            m.iter().collect::<Vec<_>>(),
+            vec![(&1, &2), (&2, &33), (&3, &4), (&5, &66), (&6, &7)]
+        );
     }
 
     #[test]
@@ -250,6 +1432,125 @@ mod tests {
         assert_eq!(m.get(&3), None);
     }
 
+    #[test]
+    fn test_remove_entry() {
+        let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
+        assert_eq!(m.remove_entry(&5), Some((5, 6)));
+        assert_eq!(m.remove_entry(&1), Some((1, 2)));
+        assert_eq!(m.remove_entry(&1), None);
+        assert_eq!(m.len(), 1);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6), (7, 8)]);
+        m.retain(|k, _| *k != 3);
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&1, &2), (&5, &6), (&7, &8)]);
+        m.retain_mut(|k, v| {
+            *v += 1;
+            *k > 1
+        });
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&5, &7), (&7, &9)]);
+    }
+
+    #[test]
+    fn test_append() {
+        let mut a = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
+        let mut b = FlatMap::from([(0, 1), (3, 44), (7, 8)]);
+        a.append(&mut b);
+        assert!(b.is_empty());
+        assert_eq!(
+            a.iter().collect::<Vec<_>>(),
+            vec![(&0, &1), (&1, &2), (&3, &44), (&5, &6), (&7, &8)]
+        );
+
+        let mut c = FlatMap::from([(8, 9)]);
+        a.append(&mut c);
+        assert_eq!(a.len(), 6);
+        assert_eq!(a.get(&8), Some(&9));
+    }
+
+    #[test]
+    fn test_remove_range() {
+        let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6), (7, 8), (9, 10)]);
+        assert_eq!(m.remove_range(2..=5).collect::<Vec<_>>(), vec![(3, 4), (5, 6)]);
+        m.remove_range(..8);
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&9, &10)]);
+    }
+
+    #[test]
+    fn test_merge_with() {
+        let a = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
+        let b = FlatMap::from([(0, 1), (3, 40), (5, 60)]);
+        let m = a.merge_with(b, |k, x, y| k + x + y);
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&0, &1), (&1, &2), (&3, &47), (&5, &71)]);
+    }
+
+    #[test]
+    fn test_concat() {
+        let lower = FlatMap::from([(1, 2), (3, 4)]);
+        let upper = FlatMap::from([(5, 6)]);
+        let m = FlatMap::concat(lower, upper).unwrap();
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&1, &2), (&3, &4), (&5, &6)]);
+        let m = FlatMap::concat(m, FlatMap::new()).unwrap();
+
+        let err = FlatMap::concat(m, FlatMap::from([(5, 7)])).unwrap_err();
+        let (lower, upper) = err.into_parts();
+        assert_eq!(lower.len(), 3);
+        assert_eq!(upper.get(&5), Some(&7));
+    }
+
+    #[test]
+    fn test_partition() {
+        let m = FlatMap::from([(1, 2), (2, 3), (3, 4), (4, 5)]);
+        let (even, odd) = m.partition(|k, _| k % 2 == 0);
+        assert_eq!(even.iter().collect::<Vec<_>>(), vec![(&2, &3), (&4, &5)]);
+        assert_eq!(odd.iter().collect::<Vec<_>>(), vec![(&1, &2), (&3, &4)]);
+    }
+
+    #[test]
+    fn test_split_off() {
+        let mut a = FlatMap::from([(1, 2), (3, 4), (5, 6), (7, 8)]);
+        let b = a.split_off(&5);
+        assert_eq!(a.iter().collect::<Vec<_>>(), vec![(&1, &2), (&3, &4)]);
+        assert_eq!(b.iter().collect::<Vec<_>>(), vec![(&5, &6), (&7, &8)]);
+
+        let c = a.split_off(&2);
+        assert_eq!(a.iter().collect::<Vec<_>>(), vec![(&1, &2)]);
+        assert_eq!(c.iter().collect::<Vec<_>>(), vec![(&3, &4)]);
+        assert!(a.split_off(&100).is_empty());
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
+        m.extend([(4, 5), (0, 1), (3, 44), (4, 55)]);
+        assert_eq!(
+            m.iter().collect::<Vec<_>>(),
+            vec![(&0, &1), (&1, &2), (&3, &44), (&4, &55), (&5, &6)]
+        );
+        m.extend([(&6, &7)]);
+        assert_eq!(m.get(&6), Some(&7));
+        m.extend(Vec::<(i32, i32)>::new());
+        assert_eq!(m.len(), 6);
+    }
+
+    #[test]
+    fn test_split_before_after() {
+        let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6), (7, 8)]);
+        let head = m.split_before(&3);
+        assert_eq!(head.iter().collect::<Vec<_>>(), vec![(&1, &2)]);
+        assert_eq!(m.keys().collect::<Vec<_>>(), vec![&3, &5, &7]);
+
+        let tail = m.split_after(&5);
+        assert_eq!(tail.iter().collect::<Vec<_>>(), vec![(&7, &8)]);
+        assert_eq!(m.keys().collect::<Vec<_>>(), vec![&3, &5]);
+
+        assert!(m.split_before(&0).is_empty());
+        assert!(m.split_after(&4).contains_key(&5));
+        assert_eq!(m.keys().collect::<Vec<_>>(), vec![&3]);
+    }
+
     #[test]
     fn test_clear() {
         let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
@@ -263,6 +1564,61 @@ mod tests {
         assert_eq!(m.len(), 3);
     }
 
+    #[test]
+    fn test_capacity() {
+        let mut m = FlatMap::with_capacity(10);
+        assert!(m.capacity() >= 10);
+        m.insert(1, 2);
+        m.reserve(20);
+        assert!(m.capacity() >= 21);
+        m.reserve_exact(30);
+        assert!(m.capacity() >= 31);
+        m.shrink_to_fit();
+        assert_eq!(m.get(&1), Some(&2));
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let mut m = FlatMap::from([(5, 6), (1, 2), (3, 4)]);
+        m.insert(2, 3);
+        m.extend([(0, 1), (7, 8)]);
+        assert_eq!(m.check_invariants(), Ok(()));
+
+        let m = FlatMap::from_parts(vec![(1, 2), (3, 4), (3, 5)], NaturalOrder);
+        assert_eq!(m.check_invariants(), Err(NotSortedError { index: 2 }));
+    }
+
+    #[test]
+    fn test_as_slice() {
+        let m = FlatMap::from([(5, 6), (1, 2), (3, 4)]);
+        assert_eq!(m.as_slice(), &[(1, 2), (3, 4), (5, 6)]);
+        assert_eq!(m.into_inner(), vec![(1, 2), (3, 4), (5, 6)]);
+    }
+
+    #[test]
+    fn test_map_values() {
+        let m = FlatMap::from([(1, 2), (3, 4)]).map_values(|k, v| format!("{k}:{v}"));
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&1, &"1:2".to_string()), (&3, &"3:4".to_string())]);
+    }
+
+    #[test]
+    fn test_keys_set() {
+        let m = FlatMap::from([(3, "c"), (1, "a"), (2, "b")]);
+        let set = m.keys_to_set();
+        assert_eq!(set.as_slice(), &[1, 2, 3]);
+        assert_eq!(m.into_keys_set(), set);
+
+        let m = FlatMap::from_keys_with(&set, |k| k * 10);
+        assert_eq!(m.as_slice(), &[(1, 10), (2, 20), (3, 30)]);
+    }
+
+    #[test]
+    fn test_freeze() {
+        let m = FlatMap::from([(1, 2), (3, 4)]).freeze();
+        assert_eq!(m.get(&3), Some(&4));
+        assert_eq!(m.thaw().into_inner(), vec![(1, 2), (3, 4)]);
+    }
+
     #[test]
     fn test_iter() {
         let m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
@@ -272,13 +1628,81 @@ mod tests {
     #[test]
     fn test_iter_mut() {
         let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
-        let mut it = m.iter_mut();
-        it.next();
-        *it.next().unwrap().1 = 22;
-        drop(it);
+        {
+            let mut it = m.iter_mut();
+            it.next();
+            *it.next().unwrap().1 = 22;
+        }
         assert_eq!(m.get(&3), Some(&22));
     }
 
+    #[test]
+    fn test_iter_double_ended() {
+        let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
+        assert_eq!(m.iter().rev().collect::<Vec<_>>(), vec![(&5, &6), (&3, &4), (&1, &2)]);
+        assert_eq!(m.iter().len(), 3);
+        assert_eq!(m.keys().next_back(), Some(&5));
+        assert_eq!(m.values().len(), 3);
+        *m.iter_mut().next_back().unwrap().1 = 66;
+        *m.values_mut().next_back().unwrap() += 1;
+        assert_eq!(m.get(&5), Some(&67));
+        assert_eq!(m.range(2..).next_back(), Some((&5, &67)));
+    }
+
+    #[test]
+    fn test_iter_from() {
+        let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
+        assert_eq!(m.iter_from(&2).collect::<Vec<_>>(), vec![(&3, &4), (&5, &6)]);
+        assert_eq!(m.iter_from(&3).len(), 2);
+        assert_eq!(m.iter_from(&6).next(), None);
+        m.iter_from_mut(&5).for_each(|(_, v)| *v = 0);
+        assert_eq!(m.get(&5), Some(&0));
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
+        for (_, v) in &mut m {
+            *v += 1;
+        }
+        assert_eq!((&m).into_iter().len(), 3);
+        assert_eq!(m.into_iter().rev().collect::<Vec<_>>(), vec![(5, 7), (3, 5), (1, 3)]);
+    }
+
+    #[test]
+    fn test_cursor() {
+        let m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
+        let mut c = m.cursor_at(&2);
+        assert_eq!(c.index(), 1);
+        assert_eq!(c.peek_prev(), Some((&1, &2)));
+        assert_eq!(c.next(), Some((&3, &4)));
+        assert_eq!(c.next(), Some((&5, &6)));
+        assert_eq!(c.next(), None);
+        assert_eq!(c.prev(), Some((&5, &6)));
+        assert_eq!(m.cursor_front().peek_prev(), None);
+        assert_eq!(m.cursor_back().peek_prev(), Some((&5, &6)));
+    }
+
+    #[test]
+    fn test_cursor_mut() {
+        let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
+        let mut c = m.cursor_at_mut(&3);
+        assert_eq!(c.insert_before(2, 3), Ok(()));
+        assert_eq!(c.insert_before(2, 3), Err(UnorderedKeyError));
+        assert_eq!(c.insert_after(4, 5), Err(UnorderedKeyError));
+        *c.next().unwrap().1 = 44;
+        assert_eq!(c.insert_after(4, 5), Ok(()));
+        assert_eq!(c.remove_next(), Some((4, 5)));
+        assert_eq!(c.remove_prev(), Some((3, 44)));
+        assert_eq!(c.as_cursor().peek_next(), Some((&5, &6)));
+
+        let mut c = m.cursor_back_mut();
+        assert_eq!(c.insert_after(9, 10), Ok(()));
+        assert_eq!(c.remove_next(), Some((9, 10)));
+        assert_eq!(c.remove_next(), None);
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&1, &2), (&2, &3), (&5, &6)]);
+    }
+
     #[test]
     fn test_keys() {
         let m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
@@ -291,6 +1715,28 @@ mod tests {
         assert_eq!(m.values().collect::<Vec<_>>(), vec![&2, &4, &6]);
     }
 
+    #[test]
+    fn test_drain() {
+        let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
+        assert_eq!(m.drain().collect::<Vec<_>>(), vec![(1, 2), (3, 4), (5, 6)]);
+        assert!(m.is_empty());
+        assert_eq!(m.insert(7, 8), None);
+        assert_eq!(m.get(&7), Some(&8));
+    }
+
+    #[test]
+    fn test_extract_if() {
+        let mut m = FlatMap::from([(1, 2), (2, 3), (3, 4), (4, 5)]);
+        let extracted = m.extract_if(|k, _| k % 2 == 0).collect::<Vec<_>>();
+        assert_eq!(extracted, vec![(2, 3), (4, 5)]);
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&1, &2), (&3, &4)]);
+
+        let mut it = m.extract_if(|_, _| true);
+        assert_eq!(it.next(), Some((1, 2)));
+        drop(it);
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&3, &4)]);
+    }
+
     #[test]
     fn test_values_mut() {
         let mut m = FlatMap::from([(1, 2), (3, 4), (5, 6)]);
@@ -0,0 +1,52 @@
+use crate::{FlatMap, FrozenFlatMap, OutOfOrderError};
+
+/// Builds a [`FlatMap`] or [`FrozenFlatMap`] from entries arriving in strictly increasing key order.
+///
+/// Every push is checked against the previous key in O(1), so nothing is buffered or re-sorted.
+pub struct FlatMapBuilder<K: Ord, V> {
+    items: Vec<(K, V)>,
+}
+
+impl<K: Ord, V> Default for FlatMapBuilder<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> FlatMapBuilder<K, V> {
+    pub fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { items: Vec::with_capacity(capacity) }
+    }
+
+    /// Appends an entry, failing if `key` is not greater than the last pushed key.
+    pub fn push(&mut self, key: K, value: V) -> Result<(), OutOfOrderError> {
+        if let Some((last, _)) = self.items.last() {
+            if *last >= key {
+                return Err(OutOfOrderError);
+            }
+        }
+
+        self.items.push((key, value));
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn finish(self) -> FlatMap<K, V> {
+        FlatMap::from_sorted_vec_unchecked(self.items)
+    }
+
+    pub fn finish_frozen(self) -> FrozenFlatMap<K, V> {
+        self.finish().freeze()
+    }
+}
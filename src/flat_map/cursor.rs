@@ -0,0 +1,145 @@
+use crate::{Compare, NaturalOrder, UnorderedKeyError};
+use std::cmp::Ordering;
+
+/// A read-only cursor over a [`FlatMap`](crate::FlatMap).
+///
+/// The cursor points at a gap between two entries (or before the first / after the last one),
+/// and since entries are stored contiguously it is just an index, moving it never searches.
+pub struct Cursor<'a, K, V> {
+    pub(super) items: &'a [(K, V)],
+    pub(super) index: usize,
+}
+
+impl<K, V> Clone for Cursor<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self { items: self.items, index: self.index }
+    }
+}
+
+impl<'a, K, V> Cursor<'a, K, V> {
+    /// Number of entries before the cursor.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the entry after the cursor and moves past it.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let (k, v) = self.items.get(self.index)?;
+        self.index += 1;
+        Some((k, v))
+    }
+
+    /// Returns the entry before the cursor and moves before it.
+    pub fn prev(&mut self) -> Option<(&'a K, &'a V)> {
+        let index = self.index.checked_sub(1)?;
+        self.index = index;
+        let (k, v) = &self.items[index];
+        Some((k, v))
+    }
+
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        self.items.get(self.index).map(|(k, v)| (k, v))
+    }
+
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        let index = self.index.checked_sub(1)?;
+        let (k, v) = &self.items[index];
+        Some((k, v))
+    }
+}
+
+/// A mutable cursor over a [`FlatMap`](crate::FlatMap).
+///
+/// Like [`Cursor`], but also allows mutating values and inserting or removing entries
+/// around the cursor position.
+pub struct CursorMut<'a, K, V, C = NaturalOrder> {
+    pub(super) items: &'a mut Vec<(K, V)>,
+    pub(super) cmp: &'a C,
+    pub(super) index: usize,
+}
+
+impl<K, V, C: Compare<K>> CursorMut<'_, K, V, C> {
+    /// Number of entries before the cursor.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the entry after the cursor and moves past it.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(&K, &mut V)> {
+        let (k, v) = self.items.get_mut(self.index)?;
+        self.index += 1;
+        Some((k, v))
+    }
+
+    /// Returns the entry before the cursor and moves before it.
+    pub fn prev(&mut self) -> Option<(&K, &mut V)> {
+        let index = self.index.checked_sub(1)?;
+        self.index = index;
+        let (k, v) = &mut self.items[index];
+        Some((k, v))
+    }
+
+    pub fn peek_next(&mut self) -> Option<(&K, &mut V)> {
+        self.items.get_mut(self.index).map(|(k, v)| (&*k, v))
+    }
+
+    pub fn peek_prev(&mut self) -> Option<(&K, &mut V)> {
+        let index = self.index.checked_sub(1)?;
+        let (k, v) = &mut self.items[index];
+        Some((k, v))
+    }
+
+    /// Returns a read-only cursor at the same position.
+    pub fn as_cursor(&self) -> Cursor<'_, K, V> {
+        Cursor { items: self.items, index: self.index }
+    }
+
+    /// Inserts an entry before the cursor, so that it becomes the previous entry.
+    ///
+    /// Fails if `key` is not strictly between the keys surrounding the cursor.
+    pub fn insert_before(&mut self, key: K, value: V) -> Result<(), UnorderedKeyError> {
+        self.check_order(&key)?;
+        self.items.insert(self.index, (key, value));
+        self.index += 1;
+        super::debug_check_order(self.items, self.cmp);
+        Ok(())
+    }
+
+    /// Inserts an entry after the cursor, so that it becomes the next entry.
+    ///
+    /// Fails if `key` is not strictly between the keys surrounding the cursor.
+    pub fn insert_after(&mut self, key: K, value: V) -> Result<(), UnorderedKeyError> {
+        self.check_order(&key)?;
+        self.items.insert(self.index, (key, value));
+        super::debug_check_order(self.items, self.cmp);
+        Ok(())
+    }
+
+    /// Removes and returns the entry after the cursor.
+    pub fn remove_next(&mut self) -> Option<(K, V)> {
+        (self.index < self.items.len()).then(|| self.items.remove(self.index))
+    }
+
+    /// Removes and returns the entry before the cursor.
+    pub fn remove_prev(&mut self) -> Option<(K, V)> {
+        let index = self.index.checked_sub(1)?;
+        self.index = index;
+        Some(self.items.remove(index))
+    }
+
+    fn check_order(&self, key: &K) -> Result<(), UnorderedKeyError> {
+        let after_prev = self.index == 0
+            || self.cmp.compare(&self.items[self.index - 1].0, key) == Ordering::Less;
+        let before_next = self.items
+                              .get(self.index)
+                              .is_none_or(|(next, _)| self.cmp.compare(key, next) == Ordering::Less);
+
+        if after_prev && before_next {
+            Ok(())
+        } else {
+            Err(UnorderedKeyError)
+        }
+    }
+}
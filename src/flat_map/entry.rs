@@ -0,0 +1,47 @@
+use std::mem;
+
+/// A handle to an entry of a [`FlatMap`](crate::FlatMap) that is known to exist,
+/// returned by [`FlatMap::first_entry`](crate::FlatMap::first_entry) and
+/// [`FlatMap::last_entry`](crate::FlatMap::last_entry).
+pub struct OccupiedEntry<'a, K, V> {
+    pub(super) items: &'a mut Vec<(K, V)>,
+    pub(super) index: usize,
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    /// Position of the entry in key order.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn key(&self) -> &K {
+        &self.items[self.index].0
+    }
+
+    pub fn get(&self) -> &V {
+        &self.items[self.index].1
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.items[self.index].1
+    }
+
+    /// Converts the handle into a reference bound to the map's lifetime.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.items[self.index].1
+    }
+
+    /// Replaces the value, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// Removes the entry, in O(1) for the last entry and O(n) otherwise.
+    pub fn remove_entry(self) -> (K, V) {
+        self.items.remove(self.index)
+    }
+}
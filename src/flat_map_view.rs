@@ -0,0 +1,184 @@
+use crate::{flat_map::{Iter, Keys, Values}, sorted, Comparable, FlatMap, FrozenFlatMap, NaturalOrder, NotSortedError};
+use std::{fmt, ops::RangeBounds};
+
+/// Read-only map over a borrowed slice sorted by key.
+///
+/// Offers the lookup API of [`FlatMap`] without owning or copying the entries, e.g. for
+/// slices living in an arena or inside another container.
+pub struct FlatMapView<'a, K: Ord, V> {
+    items: &'a [(K, V)],
+}
+
+impl<K: Ord, V> Clone for FlatMapView<'_, K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K: Ord, V> Copy for FlatMapView<'_, K, V> {}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for FlatMapView<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for FlatMapView<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: Ord, V> From<&'a FlatMap<K, V>> for FlatMapView<'a, K, V> {
+    fn from(map: &'a FlatMap<K, V>) -> Self {
+        Self::from_sorted_unchecked(map.as_slice())
+    }
+}
+
+impl<'a, K: Ord, V> From<&'a FrozenFlatMap<K, V>> for FlatMapView<'a, K, V> {
+    fn from(map: &'a FrozenFlatMap<K, V>) -> Self {
+        Self::from_sorted_unchecked(map.as_slice())
+    }
+}
+
+impl<'a, K: Ord, V> FlatMapView<'a, K, V> {
+    /// Wraps a slice that is already sorted by key and free of duplicates, without checking it.
+    ///
+    /// Lookups on a view of unsorted input return unspecified results.
+    pub const fn from_sorted_unchecked(items: &'a [(K, V)]) -> Self {
+        Self { items }
+    }
+
+    /// Wraps a slice that is already sorted by key and free of duplicates in O(n).
+    pub fn try_from_sorted(items: &'a [(K, V)]) -> Result<Self, NotSortedError> {
+        match sorted::first_unsorted(items, &NaturalOrder) {
+            Some(index) => Err(NotSortedError { index }),
+            None => Ok(Self { items }),
+        }
+    }
+
+    // lookup
+
+    pub fn contains_key<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> bool {
+        self.search(key).is_ok()
+    }
+
+    pub fn get<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<&'a V> {
+        self.search(key)
+            .ok()
+            .map(|i| &self.items[i].1)
+    }
+
+    pub fn get_key_value<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<(&'a K, &'a V)> {
+        self.search(key)
+            .ok()
+            .map(|i| {
+                let (k, v) = &self.items[i];
+                (k, v)
+            })
+    }
+
+    // positional access
+
+    /// Returns the entry at position `index` in key order.
+    pub fn get_index(&self, index: usize) -> Option<(&'a K, &'a V)> {
+        self.items.get(index).map(|(k, v)| (k, v))
+    }
+
+    /// Returns the position of `key` in key order.
+    pub fn index_of<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<usize> {
+        self.search(key).ok()
+    }
+
+    /// Returns the number of keys strictly less than `key`.
+    pub fn rank<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> usize {
+        sorted::lower_bound(self.items, key, &NaturalOrder)
+    }
+
+    // ranges
+
+    pub fn range<Q: ?Sized + Comparable<K>>(&self, range: impl RangeBounds<Q>) -> Iter<'a, K, V> {
+        let range = sorted::range_indices(self.items, range, &NaturalOrder);
+        Iter { inner: self.items[range].iter() }
+    }
+
+    /// Returns a view of the entries within `range`, sharing the same slice.
+    pub fn subview<Q: ?Sized + Comparable<K>>(&self, range: impl RangeBounds<Q>) -> Self {
+        let range = sorted::range_indices(self.items, range, &NaturalOrder);
+        Self { items: &self.items[range] }
+    }
+
+    // misc
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns the entries as a slice sorted by key.
+    pub fn as_slice(&self) -> &'a [(K, V)] {
+        self.items
+    }
+
+    // iterators
+
+    pub fn iter(&self) -> Iter<'a, K, V> {
+        Iter { inner: self.items.iter() }
+    }
+
+    pub fn keys(&self) -> Keys<'a, K, V> {
+        Keys { inner: self.items.iter() }
+    }
+
+    pub fn values(&self) -> Values<'a, K, V> {
+        Values { inner: self.items.iter() }
+    }
+
+    // internals
+
+    fn search<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Result<usize, usize> {
+        sorted::search(self.items, key, &NaturalOrder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_from_sorted() {
+        let items = [(1, 'a'), (3, 'c'), (5, 'e')];
+        let view = FlatMapView::try_from_sorted(&items).unwrap();
+        assert_eq!(view.len(), 3);
+        assert_eq!(FlatMapView::try_from_sorted(&[(2, 'b'), (1, 'a')]).unwrap_err().index(), 1);
+    }
+
+    #[test]
+    fn test_get() {
+        static ITEMS: [(&str, u32); 3] = [("a", 1), ("b", 2), ("c", 3)];
+        const VIEW: FlatMapView<'static, &str, u32> = FlatMapView::from_sorted_unchecked(&ITEMS);
+        assert!(VIEW.contains_key("b"));
+        assert_eq!(VIEW.get("c"), Some(&3));
+        assert_eq!(VIEW.get("d"), None);
+        assert_eq!(VIEW.get_key_value("a"), Some((&"a", &1)));
+        assert_eq!(VIEW.index_of("b"), Some(1));
+        assert_eq!(VIEW.get_index(2), Some((&"c", &3)));
+    }
+
+    #[test]
+    fn test_range() {
+        let m = FlatMap::from([(1, 2), (3, 4), (5, 6), (7, 8)]);
+        let view = FlatMapView::from(&m);
+        assert_eq!(view.range(2..=5).collect::<Vec<_>>(), vec![(&3, &4), (&5, &6)]);
+        let sub = view.subview(3..);
+        assert_eq!(sub.keys().collect::<Vec<_>>(), vec![&3, &5, &7]);
+        assert_eq!(sub.rank(&6), 2);
+        assert_eq!(sub.values().next_back(), Some(&8));
+    }
+}
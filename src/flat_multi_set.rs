@@ -0,0 +1,318 @@
+use crate::{sorted, Comparable, NaturalOrder};
+use std::{cmp::Ordering, fmt, ops::{Add, BitAnd, BitOr, Bound, Range, RangeBounds, Sub}, slice, vec};
+
+/// A multiset backed by a sorted `Vec` of keys, where equal keys are stored side by side.
+///
+/// Equal keys keep their insertion order. Multisets are compared and hashed by their keys in
+/// order.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FlatMultiSet<K: Ord> {
+    keys: Vec<K>,
+}
+
+impl<K: Ord + fmt::Debug> fmt::Debug for FlatMultiSet<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord> From<Vec<K>> for FlatMultiSet<K> {
+    fn from(mut keys: Vec<K>) -> Self {
+        keys.sort();
+        Self { keys }
+    }
+}
+
+impl<K: Ord, const N: usize> From<[K; N]> for FlatMultiSet<K> {
+    fn from(keys: [K; N]) -> Self {
+        Self::from(Vec::from(keys))
+    }
+}
+
+impl<K: Ord> FromIterator<K> for FlatMultiSet<K> {
+    fn from_iter<I: IntoIterator<Item=K>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+/// Appends all keys and restores the order with a single sort, which merges the two sorted
+/// runs in O(n + m log m).
+impl<K: Ord> Extend<K> for FlatMultiSet<K> {
+    fn extend<I: IntoIterator<Item=K>>(&mut self, iter: I) {
+        self.keys.extend(iter);
+        self.keys.sort();
+    }
+}
+
+impl<K: Ord> IntoIterator for FlatMultiSet<K> {
+    type Item = K;
+    type IntoIter = vec::IntoIter<K>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.keys.into_iter()
+    }
+}
+
+impl<'a, K: Ord> IntoIterator for &'a FlatMultiSet<K> {
+    type Item = &'a K;
+    type IntoIter = slice::Iter<'a, K>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Returns the multiset union, keeping the larger count of every key.
+impl<K: Ord + Clone> BitOr<&FlatMultiSet<K>> for &FlatMultiSet<K> {
+    type Output = FlatMultiSet<K>;
+
+    fn bitor(self, rhs: &FlatMultiSet<K>) -> FlatMultiSet<K> {
+        self.combine(rhs, usize::max)
+    }
+}
+
+/// Returns the multiset intersection, keeping the smaller count of every key.
+impl<K: Ord + Clone> BitAnd<&FlatMultiSet<K>> for &FlatMultiSet<K> {
+    type Output = FlatMultiSet<K>;
+
+    fn bitand(self, rhs: &FlatMultiSet<K>) -> FlatMultiSet<K> {
+        self.combine(rhs, usize::min)
+    }
+}
+
+/// Returns the multiset sum, adding up the counts of every key.
+impl<K: Ord + Clone> Add<&FlatMultiSet<K>> for &FlatMultiSet<K> {
+    type Output = FlatMultiSet<K>;
+
+    fn add(self, rhs: &FlatMultiSet<K>) -> FlatMultiSet<K> {
+        self.combine(rhs, |a, b| a + b)
+    }
+}
+
+/// Returns the multiset difference, subtracting the counts in `rhs` down to zero.
+impl<K: Ord + Clone> Sub<&FlatMultiSet<K>> for &FlatMultiSet<K> {
+    type Output = FlatMultiSet<K>;
+
+    fn sub(self, rhs: &FlatMultiSet<K>) -> FlatMultiSet<K> {
+        self.combine(rhs, usize::saturating_sub)
+    }
+}
+
+impl<K: Ord> Default for FlatMultiSet<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord> FlatMultiSet<K> {
+    pub fn new() -> Self {
+        Self { keys: Vec::new() }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { keys: Vec::with_capacity(capacity) }
+    }
+
+    // lookup
+
+    pub fn contains<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> bool {
+        self.count(key) > 0
+    }
+
+    /// Returns the number of stored keys equal to `key`, in O(log n).
+    pub fn count<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> usize {
+        self.equal_range(key).len()
+    }
+
+    // modification
+
+    /// Inserts `key` after all stored keys equal to it.
+    pub fn insert(&mut self, key: K) {
+        let i = self.keys.partition_point(|k| *k <= key);
+        self.keys.insert(i, key);
+    }
+
+    /// Removes and returns the first stored key equal to `key`.
+    pub fn remove_one<Q: ?Sized + Comparable<K>>(&mut self, key: &Q) -> Option<K> {
+        let range = self.equal_range(key);
+        (!range.is_empty()).then(|| self.keys.remove(range.start))
+    }
+
+    /// Removes all stored keys equal to `key` with a single shift of the tail, returning how
+    /// many there were.
+    pub fn remove_all<Q: ?Sized + Comparable<K>>(&mut self, key: &Q) -> usize {
+        let range = self.equal_range(key);
+        let count = range.len();
+        self.keys.drain(range);
+        count
+    }
+
+    /// Keeps only the keys for which `f` returns `true`, in a single pass.
+    pub fn retain<F: FnMut(&K) -> bool>(&mut self, f: F) {
+        self.keys.retain(f);
+    }
+
+    // ranges
+
+    pub fn range<Q: ?Sized + Comparable<K>>(&self, range: impl RangeBounds<Q>) -> slice::Iter<'_, K> {
+        let range = sorted::key_range_indices(&self.keys, range, &NaturalOrder);
+        self.keys[range].iter()
+    }
+
+    // misc
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    /// Returns the number of stored keys, counting duplicates.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.keys.capacity()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.keys.reserve(additional);
+    }
+
+    /// Returns the keys as a sorted slice.
+    pub fn as_slice(&self) -> &[K] {
+        &self.keys
+    }
+
+    /// Returns the keys as a sorted vector, without copying.
+    pub fn into_vec(self) -> Vec<K> {
+        self.keys
+    }
+
+    // iterators
+
+    pub fn iter(&self) -> slice::Iter<'_, K> {
+        self.keys.iter()
+    }
+
+    /// Iterates over the distinct keys in order, each with the number of times it is stored.
+    pub fn iter_counts(&self) -> impl Iterator<Item=(&K, usize)> + '_ {
+        self.keys.chunk_by(|a, b| a == b).map(|run| (&run[0], run.len()))
+    }
+
+    // internals
+
+    fn equal_range<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Range<usize> {
+        sorted::key_range_indices::<_, Q, _>(&self.keys, (Bound::Included(key), Bound::Included(key)), &NaturalOrder)
+    }
+
+    /// Merges two multisets run by run, keeping `count(a, b)` copies of every key, where `a`
+    /// and `b` are its counts in `self` and `other`.
+    ///
+    /// Copies are taken from `self` first.
+    fn combine(&self, other: &Self, count: impl Fn(usize, usize) -> usize) -> Self
+    where
+        K: Clone,
+    {
+        let run = |keys: &[K]| keys.partition_point(|k| *k <= keys[0]);
+
+        let mut keys = Vec::new();
+        let (mut ours, mut theirs) = (self.as_slice(), other.as_slice());
+        while !ours.is_empty() || !theirs.is_empty() {
+            let ordering = match (ours.first(), theirs.first()) {
+                (Some(a), Some(b)) => a.cmp(b),
+                (Some(_), None) => Ordering::Less,
+                _ => Ordering::Greater,
+            };
+            let a = if ordering.is_le() { run(ours) } else { 0 };
+            let b = if ordering.is_ge() { run(theirs) } else { 0 };
+            let n = count(a, b);
+            keys.extend_from_slice(&ours[..n.min(a)]);
+            keys.extend_from_slice(&theirs[..n.saturating_sub(a).min(b)]);
+            ours = &ours[a..];
+            theirs = &theirs[b..];
+        }
+        Self { keys }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traits() {
+        let m = FlatMultiSet::from([2, 1, 2]);
+        assert_eq!(format!("{m:?}"), "{1, 2, 2}");
+        assert_eq!(m.clone(), [1, 2, 2].into_iter().collect());
+        assert!(m < FlatMultiSet::from([1, 3]));
+        assert_eq!(FlatMultiSet::<i32>::default(), FlatMultiSet::new());
+        assert_eq!((&m).into_iter().len(), 3);
+        assert_eq!(m.into_iter().collect::<Vec<_>>(), vec![1, 2, 2]);
+    }
+
+    #[test]
+    fn test_count() {
+        let m = FlatMultiSet::from([3, 1, 3, 2, 3]);
+        assert_eq!(m.count(&3), 3);
+        assert_eq!(m.count(&1), 1);
+        assert_eq!(m.count(&0), 0);
+        assert!(m.contains(&2));
+        assert!(!m.contains(&4));
+        assert_eq!(m.len(), 5);
+        assert_eq!(m.iter_counts().collect::<Vec<_>>(), vec![(&1, 1), (&2, 1), (&3, 3)]);
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut m = FlatMultiSet::new();
+        m.insert((1, "a"));
+        m.insert((0, "b"));
+        m.insert((1, "a"));
+        assert_eq!(m.count(&(1, "a")), 2);
+        assert_eq!(m.remove_one(&(1, "a")), Some((1, "a")));
+        assert_eq!(m.remove_one(&(2, "c")), None);
+        assert_eq!(m.len(), 2);
+
+        let mut m = FlatMultiSet::from([1, 2, 2, 2, 3]);
+        assert_eq!(m.remove_all(&2), 3);
+        assert_eq!(m.remove_all(&2), 0);
+        assert_eq!(m.as_slice(), &[1, 3]);
+        m.extend([3, 0]);
+        assert_eq!(m.into_vec(), vec![0, 1, 3, 3]);
+    }
+
+    #[test]
+    fn test_borrowed() {
+        let mut m = FlatMultiSet::from(["a".to_string(), "a".to_string()]);
+        assert_eq!(m.count("a"), 2);
+        assert_eq!(m.remove_one("a"), Some("a".to_string()));
+        assert_eq!(m.remove_all("a"), 1);
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_operators() {
+        let a = FlatMultiSet::from([1, 1, 2, 3, 3, 3]);
+        let b = FlatMultiSet::from([1, 3, 3, 4]);
+        assert_eq!((&a | &b).into_vec(), vec![1, 1, 2, 3, 3, 3, 4]);
+        assert_eq!((&a & &b).into_vec(), vec![1, 3, 3]);
+        assert_eq!((&a + &b).into_vec(), vec![1, 1, 1, 2, 3, 3, 3, 3, 3, 4]);
+        assert_eq!((&a - &b).into_vec(), vec![1, 2, 3]);
+        assert_eq!((&b - &a).into_vec(), vec![4]);
+        assert!((&a & &FlatMultiSet::new()).is_empty());
+    }
+
+    #[test]
+    fn test_range() {
+        let mut m = FlatMultiSet::from([1, 2, 2, 5, 7]);
+        assert_eq!(m.range(2..6).collect::<Vec<_>>(), vec![&2, &2, &5]);
+        m.retain(|k| k % 2 == 0);
+        assert_eq!(m.as_slice(), &[2, 2]);
+        m.clear();
+        assert!(m.is_empty());
+    }
+}
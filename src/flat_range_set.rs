@@ -0,0 +1,223 @@
+use crate::Comparable;
+use std::{cmp::Ordering, fmt, ops::{BitAnd, BitOr, Range}};
+
+/// A set of disjoint half-open ranges of keys, backed by a sorted `Vec` of their boundaries.
+///
+/// Starts and ends alternate in the boundary array, so a key lies inside the set when an odd
+/// number of boundaries are less than or equal to it. Overlapping and touching ranges are
+/// coalesced, so two sets covering the same keys compare equal.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct FlatRangeSet<K> {
+    bounds: Vec<K>,
+}
+
+impl<K: Ord + fmt::Debug> fmt::Debug for FlatRangeSet<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter_ranges()).finish()
+    }
+}
+
+impl<K: Ord> FromIterator<Range<K>> for FlatRangeSet<K> {
+    fn from_iter<I: IntoIterator<Item=Range<K>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for range in iter {
+            set.insert_range(range);
+        }
+        set
+    }
+}
+
+/// Returns the union of two range sets in a single sweep over their boundaries.
+impl<K: Ord + Clone> BitOr<&FlatRangeSet<K>> for &FlatRangeSet<K> {
+    type Output = FlatRangeSet<K>;
+
+    fn bitor(self, rhs: &FlatRangeSet<K>) -> FlatRangeSet<K> {
+        self.combine(rhs, |a, b| a || b)
+    }
+}
+
+/// Returns the intersection of two range sets in a single sweep over their boundaries.
+impl<K: Ord + Clone> BitAnd<&FlatRangeSet<K>> for &FlatRangeSet<K> {
+    type Output = FlatRangeSet<K>;
+
+    fn bitand(self, rhs: &FlatRangeSet<K>) -> FlatRangeSet<K> {
+        self.combine(rhs, |a, b| a && b)
+    }
+}
+
+impl<K> Default for FlatRangeSet<K> {
+    fn default() -> Self {
+        Self { bounds: Vec::new() }
+    }
+}
+
+impl<K: Ord> FlatRangeSet<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // lookup
+
+    /// Returns whether `key` lies inside one of the ranges, in O(log n).
+    pub fn contains<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> bool {
+        self.bounds.partition_point(|b| key.compare(b) != Ordering::Less) % 2 == 1
+    }
+
+    // modification
+
+    /// Adds all keys in `range`, merging it with the ranges it overlaps or touches, in O(n).
+    ///
+    /// Empty ranges are ignored.
+    pub fn insert_range(&mut self, range: Range<K>) {
+        self.assign(range, true);
+    }
+
+    /// Removes all keys in `range`, splitting the ranges it partially overlaps, in O(n).
+    pub fn remove_range(&mut self, range: Range<K>) {
+        self.assign(range, false);
+    }
+
+    // misc
+
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.bounds.clear();
+    }
+
+    /// Returns the number of disjoint ranges.
+    pub fn len(&self) -> usize {
+        self.bounds.len() / 2
+    }
+
+    // iterators
+
+    /// Iterates over the disjoint ranges in order.
+    pub fn iter_ranges(&self) -> impl DoubleEndedIterator<Item=Range<&K>> + ExactSizeIterator + '_ {
+        self.bounds.chunks_exact(2).map(|r| &r[0]..&r[1])
+    }
+
+    /// Iterates over the gaps between consecutive ranges in order.
+    pub fn gaps(&self) -> impl DoubleEndedIterator<Item=Range<&K>> + ExactSizeIterator + '_ {
+        self.bounds.get(1..).unwrap_or_default().chunks_exact(2).map(|r| &r[0]..&r[1])
+    }
+
+    // internals
+
+    /// Makes every key in `range` inside the set or outside of it, replacing all boundaries
+    /// within it with at most a new end and a new start.
+    fn assign(&mut self, range: Range<K>, inside: bool) {
+        if range.start >= range.end {
+            return;
+        }
+
+        let lo = self.bounds.partition_point(|b| *b < range.start);
+        let hi = self.bounds.partition_point(|b| *b <= range.end);
+        let start = (lo % 2 == 0) == inside;
+        let end = (hi % 2 == 0) == inside;
+        let new = [start.then_some(range.start), end.then_some(range.end)];
+        self.bounds.splice(lo..hi, new.into_iter().flatten());
+    }
+
+    /// Sweeps over the boundaries of both sets, keeping the keys for which `op` of their
+    /// membership in `self` and `other` is `true`.
+    fn combine(&self, other: &Self, op: impl Fn(bool, bool) -> bool) -> Self
+    where
+        K: Clone,
+    {
+        let (ours, theirs) = (&self.bounds, &other.bounds);
+        let (mut i, mut j) = (0, 0);
+        let mut inside = false;
+        let mut bounds = Vec::new();
+        loop {
+            let next = match (ours.get(i), theirs.get(j)) {
+                (Some(a), Some(b)) => a.min(b),
+                (Some(a), None) => a,
+                (None, Some(b)) => b,
+                (None, None) => break,
+            };
+            let now = (ours.get(i) == Some(next), theirs.get(j) == Some(next));
+            i += now.0 as usize;
+            j += now.1 as usize;
+            if op(i % 2 == 1, j % 2 == 1) != inside {
+                inside = !inside;
+                bounds.push(next.clone());
+            }
+        }
+        Self { bounds }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(s: &FlatRangeSet<u32>) -> Vec<(u32, u32)> {
+        s.iter_ranges().map(|r| (*r.start, *r.end)).collect()
+    }
+
+    #[test]
+    fn test_contains() {
+        let s = FlatRangeSet::from_iter([10..20, 30..40]);
+        assert!(!s.contains(&9));
+        assert!(s.contains(&10));
+        assert!(s.contains(&19));
+        assert!(!s.contains(&20));
+        assert!(s.contains(&30));
+        assert!(!s.contains(&40));
+        assert_eq!(s.len(), 2);
+        assert_eq!(format!("{s:?}"), "{10..20, 30..40}");
+    }
+
+    #[test]
+    fn test_insert_range() {
+        let mut s = FlatRangeSet::new();
+        s.insert_range(10..20);
+        s.insert_range(30..40);
+        s.insert_range(20..25);
+        assert_eq!(ranges(&s), vec![(10, 25), (30, 40)]);
+        s.insert_range(5..10);
+        s.insert_range(12..15);
+        s.insert_range(50..50);
+        assert_eq!(ranges(&s), vec![(5, 25), (30, 40)]);
+        s.insert_range(0..100);
+        assert_eq!(ranges(&s), vec![(0, 100)]);
+    }
+
+    #[test]
+    fn test_remove_range() {
+        let mut s = FlatRangeSet::from_iter(std::iter::once(0..100));
+        s.remove_range(40..60);
+        assert_eq!(ranges(&s), vec![(0, 40), (60, 100)]);
+        s.remove_range(30..40);
+        s.remove_range(90..200);
+        assert_eq!(ranges(&s), vec![(0, 30), (60, 90)]);
+        s.remove_range(0..30);
+        assert_eq!(ranges(&s), vec![(60, 90)]);
+        s.remove_range(0..10);
+        s.remove_range(60..90);
+        assert!(s.is_empty());
+        assert_eq!(s, FlatRangeSet::new());
+    }
+
+    #[test]
+    fn test_gaps() {
+        let s = FlatRangeSet::from_iter([0..10, 20..30, 35..40]);
+        let gaps = s.gaps().map(|r| (*r.start, *r.end)).collect::<Vec<_>>();
+        assert_eq!(gaps, vec![(10, 20), (30, 35)]);
+        assert_eq!(FlatRangeSet::<u32>::new().gaps().len(), 0);
+        assert_eq!(FlatRangeSet::from_iter(std::iter::once(0..10)).gaps().len(), 0);
+    }
+
+    #[test]
+    fn test_operators() {
+        let a = FlatRangeSet::from_iter([0..10, 20..30, 40..50]);
+        let b = FlatRangeSet::from_iter([5..20, 45..60]);
+        assert_eq!(ranges(&(&a | &b)), vec![(0, 30), (40, 60)]);
+        assert_eq!(ranges(&(&a & &b)), vec![(5, 10), (45, 50)]);
+        assert_eq!(&a | &FlatRangeSet::new(), a);
+        assert!((&a & &FlatRangeSet::new()).is_empty());
+    }
+}
@@ -1,30 +1,47 @@
-use crate::FlatMap;
+mod expr;
+mod iter;
 
-struct NoValue;
+pub use expr::SetExpr;
+pub use iter::{IntoIter, Iter, Range};
 
+use iter::Merge;
+
+use crate::{sorted, Comparable, NaturalOrder, NotSortedError};
+use std::{cmp::{Ordering, Reverse}, collections::BinaryHeap, fmt, mem, ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, RangeBounds, Sub, SubAssign}, vec};
+
+/// A set backed by a sorted `Vec` of keys.
+///
+/// Sets are compared and hashed by their keys in order.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct FlatSet<K: Ord> {
-    inner: FlatMap<K, NoValue>,
+    keys: Vec<K>,
+}
+
+impl<K: Ord + fmt::Debug> fmt::Debug for FlatSet<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
 }
 
+/// Sorts the keys, keeping only the last of several equal keys.
 impl<K: Ord> From<Vec<K>> for FlatSet<K> {
-    fn from(mut values: Vec<K>) -> Self {
-        FlatSet {
-            inner: FlatMap::from(values.drain(..)
-                                       .map(|k| (k, NoValue))
-                                       .collect::<Vec<_>>())
-        }
+    fn from(mut keys: Vec<K>) -> Self {
+        keys.reverse();
+        keys.sort();
+        keys.dedup();
+        Self { keys }
     }
 }
 
 impl<K: Ord + Clone> From<&[K]> for FlatSet<K> {
-    fn from(values: &[K]) -> Self {
-        Self::from(values.to_vec())
+    fn from(keys: &[K]) -> Self {
+        Self::from(keys.to_vec())
     }
 }
 
-impl<K: Ord + Clone, const N: usize> From<[K; N]> for FlatSet<K> {
-    fn from(value: [K; N]) -> Self {
-        Self::from(value.to_vec())
+impl<K: Ord, const N: usize> From<[K; N]> for FlatSet<K> {
+    fn from(keys: [K; N]) -> Self {
+        Self::from(Vec::from(keys))
     }
 }
 
@@ -34,56 +51,587 @@ impl<K: Ord> FromIterator<K> for FlatSet<K> {
     }
 }
 
+/// Inserts all keys with a single sort and merge, like [`FlatMap::insert_many`](crate::FlatMap::insert_many).
+impl<K: Ord> Extend<K> for FlatSet<K> {
+    fn extend<I: IntoIterator<Item=K>>(&mut self, iter: I) {
+        let other = Self::from_iter(iter);
+        let keys = mem::take(&mut self.keys);
+        *self = Self::combine(keys.into_iter(), other.keys.into_iter(), [true, true, true]);
+    }
+}
+
+impl<'a, K: Ord + Copy> Extend<&'a K> for FlatSet<K> {
+    fn extend<I: IntoIterator<Item=&'a K>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<K: Ord> IntoIterator for FlatSet<K> {
+    type Item = K;
+    type IntoIter = IntoIter<K>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { inner: self.keys.into_iter() }
+    }
+}
+
+impl<'a, K: Ord> IntoIterator for &'a FlatSet<K> {
+    type Item = &'a K;
+    type IntoIter = Iter<'a, K>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Returns the union of two sets in a single merge pass.
+impl<K: Ord + Clone> BitOr<&FlatSet<K>> for &FlatSet<K> {
+    type Output = FlatSet<K>;
+
+    fn bitor(self, rhs: &FlatSet<K>) -> FlatSet<K> {
+        FlatSet::combine(self.iter().cloned(), rhs.iter().cloned(), [true, true, true])
+    }
+}
+
+impl<K: Ord + Clone> BitOrAssign<&FlatSet<K>> for FlatSet<K> {
+    fn bitor_assign(&mut self, rhs: &FlatSet<K>) {
+        self.union_with(rhs);
+    }
+}
+
+/// Returns the intersection of two sets in a single merge pass.
+impl<K: Ord + Clone> BitAnd<&FlatSet<K>> for &FlatSet<K> {
+    type Output = FlatSet<K>;
+
+    fn bitand(self, rhs: &FlatSet<K>) -> FlatSet<K> {
+        FlatSet::combine(self.iter().cloned(), rhs.iter().cloned(), [false, true, false])
+    }
+}
+
+impl<K: Ord> BitAndAssign<&FlatSet<K>> for FlatSet<K> {
+    fn bitand_assign(&mut self, rhs: &FlatSet<K>) {
+        self.intersect_with(rhs);
+    }
+}
+
+/// Returns the difference of two sets in a single merge pass.
+impl<K: Ord + Clone> Sub<&FlatSet<K>> for &FlatSet<K> {
+    type Output = FlatSet<K>;
+
+    fn sub(self, rhs: &FlatSet<K>) -> FlatSet<K> {
+        FlatSet::combine(self.iter().cloned(), rhs.iter().cloned(), [true, false, false])
+    }
+}
+
+impl<K: Ord> SubAssign<&FlatSet<K>> for FlatSet<K> {
+    fn sub_assign(&mut self, rhs: &FlatSet<K>) {
+        self.difference_with(rhs);
+    }
+}
+
+/// Returns the symmetric difference of two sets in a single merge pass.
+impl<K: Ord + Clone> BitXor<&FlatSet<K>> for &FlatSet<K> {
+    type Output = FlatSet<K>;
+
+    fn bitxor(self, rhs: &FlatSet<K>) -> FlatSet<K> {
+        FlatSet::combine(self.iter().cloned(), rhs.iter().cloned(), [true, false, true])
+    }
+}
+
+impl<K: Ord + Clone> BitXorAssign<&FlatSet<K>> for FlatSet<K> {
+    fn bitxor_assign(&mut self, rhs: &FlatSet<K>) {
+        let keys = mem::take(&mut self.keys);
+        *self = FlatSet::combine(keys.into_iter(), rhs.iter().cloned(), [true, false, true]);
+    }
+}
+
+impl<K: Ord> Default for FlatSet<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<K: Ord> FlatSet<K> {
     pub fn new() -> Self {
-        Self { inner: FlatMap::new() }
+        Self { keys: Vec::new() }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { keys: Vec::with_capacity(capacity) }
+    }
+
+    /// Wraps a vector that is already sorted and free of duplicates, without checking it.
+    ///
+    /// Lookups on a set built from unsorted input return unspecified results.
+    /// The order is verified with the `debug-invariants` feature.
+    pub fn from_sorted_vec_unchecked(keys: Vec<K>) -> Self {
+        let set = Self { keys };
+        set.debug_check_invariants();
+        set
+    }
+
+    /// Wraps a vector that is already sorted and free of duplicates in O(n).
+    pub fn try_from_sorted(keys: Vec<K>) -> Result<Self, NotSortedError> {
+        match Self::first_unsorted(&keys) {
+            Some(index) => Err(NotSortedError { index }),
+            None => Ok(Self { keys }),
+        }
+    }
+
+    /// Returns the union of all `sets` with a single k-way merge, in O(n log k) for n keys
+    /// in total.
+    pub fn union_all(sets: &[&Self]) -> Self
+    where
+        K: Clone,
+    {
+        let mut keys: Vec<K> = Vec::with_capacity(sets.iter().map(|s| s.len()).max().unwrap_or(0));
+        let mut positions = vec![0; sets.len()];
+        let mut heap = sets
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| Some(Reverse((s.keys.first()?, i))))
+            .collect::<BinaryHeap<_>>();
+
+        while let Some(Reverse((key, i))) = heap.pop() {
+            positions[i] += 1;
+            if let Some(next) = sets[i].keys.get(positions[i]) {
+                heap.push(Reverse((next, i)));
+            }
+            if keys.last() != Some(key) {
+                keys.push(key.clone());
+            }
+        }
+        Self { keys }
+    }
+
+    /// Returns the intersection of all `sets`, or an empty set if there are none.
+    ///
+    /// The keys of the smallest set are looked up in the others, from smallest to largest,
+    /// with one galloping pass per set that stops as soon as any of them runs out.
+    pub fn intersection_all(sets: &[&Self]) -> Self
+    where
+        K: Clone,
+    {
+        let mut sets = sets.to_vec();
+        sets.sort_by_key(|s| s.len());
+        let Some((smallest, others)) = sets.split_first() else {
+            return Self::new();
+        };
+
+        let mut keys = Vec::new();
+        let mut starts = vec![0; others.len()];
+        'keys: for key in &smallest.keys {
+            for (set, start) in others.iter().zip(&mut starts) {
+                *start += sorted::key_gallop(&set.keys[*start..], key, &NaturalOrder);
+                match set.keys.get(*start) {
+                    None => break 'keys,
+                    Some(k) if k != key => continue 'keys,
+                    Some(_) => {}
+                }
+            }
+            keys.push(key.clone());
+        }
+        Self { keys }
     }
 
     // lookup
 
-    pub fn contains(&self, key: &K) -> bool {
-        self.inner.contains_key(key)
+    pub fn contains<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> bool {
+        self.search(key).is_ok()
+    }
+
+    /// Returns the stored key equal to `key`.
+    pub fn get<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<&K> {
+        self.search(key).ok().map(|i| &self.keys[i])
+    }
+
+    /// Returns the greatest key less than or equal to `key`.
+    pub fn get_floor<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<&K> {
+        let end = self.keys.partition_point(|k| key.compare(k) != Ordering::Less);
+        self.keys.get(end.checked_sub(1)?)
+    }
+
+    /// Returns the least key greater than or equal to `key`.
+    pub fn get_ceiling<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<&K> {
+        self.keys.get(self.rank(key))
+    }
+
+    /// Returns whether all of `keys` are in the set.
+    ///
+    /// The keys are sorted and checked with a single galloping pass over the set that stops
+    /// at the first missing key.
+    pub fn contains_all<'a>(&self, keys: impl IntoIterator<Item=&'a K>) -> bool
+    where
+        K: 'a,
+    {
+        self.probe(keys, false).is_none()
+    }
+
+    /// Returns whether any of `keys` is in the set, with a single galloping pass that stops
+    /// at the first present key.
+    pub fn contains_any<'a>(&self, keys: impl IntoIterator<Item=&'a K>) -> bool
+    where
+        K: 'a,
+    {
+        self.probe(keys, true).is_some()
+    }
+
+    /// Returns whether every key of `self` is in `other`, with a single merge pass.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        if self.len() > other.len() {
+            return false;
+        }
+        let mut theirs = other.iter();
+        self.iter().all(|key| theirs.find(|k| *k >= key) == Some(key))
+    }
+
+    /// Returns whether every key of `other` is in `self`, with a single merge pass.
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Returns whether `self` and `other` have no keys in common, with a single merge pass.
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        let mut ours = self.iter().peekable();
+        let mut theirs = other.iter().peekable();
+        while let (Some(a), Some(b)) = (ours.peek(), theirs.peek()) {
+            match a.cmp(b) {
+                Ordering::Less => ours.next(),
+                Ordering::Greater => theirs.next(),
+                Ordering::Equal => return false,
+            };
+        }
+        true
+    }
+
+    /// Returns the size of the intersection of `self` and `other`, with a single merge pass
+    /// and without allocating.
+    pub fn intersection_len(&self, other: &Self) -> usize {
+        let mut ours = self.iter().peekable();
+        let mut theirs = other.iter().peekable();
+        let mut len = 0;
+        while let (Some(a), Some(b)) = (ours.peek(), theirs.peek()) {
+            match a.cmp(b) {
+                Ordering::Less => ours.next(),
+                Ordering::Greater => theirs.next(),
+                Ordering::Equal => {
+                    len += 1;
+                    theirs.next();
+                    ours.next()
+                }
+            };
+        }
+        len
+    }
+
+    /// Returns the size of the union of `self` and `other`, with a single merge pass and
+    /// without allocating.
+    pub fn union_len(&self, other: &Self) -> usize {
+        self.len() + other.len() - self.intersection_len(other)
     }
 
     // modification
 
     pub fn insert(&mut self, key: K) -> bool {
-        self.inner.insert(key, NoValue).is_none()
+        if self.keys.last().is_none_or(|last| *last < key) {
+            self.keys.push(key);
+            return true;
+        }
+        match self.search(&key) {
+            Ok(_) => false,
+            Err(i) => {
+                self.keys.insert(i, key);
+                true
+            }
+        }
+    }
+
+    /// Returns the stored key equal to `key`, inserting `key` first if it is missing.
+    pub fn get_or_insert(&mut self, key: K) -> &K {
+        let i = match self.search(&key) {
+            Ok(i) => i,
+            Err(i) => {
+                self.keys.insert(i, key);
+                i
+            }
+        };
+        &self.keys[i]
+    }
+
+    /// Returns the stored key equal to `key`, inserting `f(key)` first if it is missing.
+    ///
+    /// The key built by `f` must be equal to `key`, which is checked in debug builds.
+    pub fn get_or_insert_with<Q, F>(&mut self, key: &Q, f: F) -> &K
+    where
+        Q: ?Sized + Comparable<K>,
+        F: FnOnce(&Q) -> K,
+    {
+        let i = match self.search(key) {
+            Ok(i) => i,
+            Err(i) => {
+                let new = f(key);
+                debug_assert!(key.compare(&new) == Ordering::Equal, "get_or_insert_with built a different key");
+                self.keys.insert(i, new);
+                i
+            }
+        };
+        &self.keys[i]
     }
 
-    pub fn remove(&mut self, key: &K) {
-        self.inner.remove(key);
+    /// Removes `key`, returning whether it was present.
+    pub fn remove<Q: ?Sized + Comparable<K>>(&mut self, key: &Q) -> bool {
+        self.take(key).is_some()
+    }
+
+    /// Removes and returns the stored key equal to `key`.
+    pub fn take<Q: ?Sized + Comparable<K>>(&mut self, key: &Q) -> Option<K> {
+        self.search(key).ok().map(|i| self.keys.remove(i))
+    }
+
+    /// Inserts `key`, replacing and returning the stored key equal to it, if any.
+    pub fn replace(&mut self, key: K) -> Option<K> {
+        match self.search(&key) {
+            Ok(i) => Some(mem::replace(&mut self.keys[i], key)),
+            Err(i) => {
+                self.keys.insert(i, key);
+                None
+            }
+        }
+    }
+
+    /// Keeps only the keys for which `f` returns `true`, in a single pass.
+    pub fn retain<F: FnMut(&K) -> bool>(&mut self, f: F) {
+        self.keys.retain(f);
+    }
+
+    /// Removes all keys within `range` with a single shift of the tail, yielding them in order.
+    ///
+    /// The keys are removed even if the returned iterator is not consumed.
+    pub fn remove_range<Q: ?Sized + Comparable<K>>(&mut self, range: impl RangeBounds<Q>) -> vec::Drain<'_, K> {
+        let range = sorted::key_range_indices(&self.keys, range, &NaturalOrder);
+        self.keys.drain(range)
+    }
+
+    /// Moves all keys from `other` into `self` in O(n + m), leaving `other` empty.
+    pub fn append(&mut self, other: &mut Self) {
+        let keys = mem::take(&mut self.keys);
+        *self = Self::combine(keys.into_iter(), other.keys.drain(..), [true, true, true]);
+    }
+
+    /// Splits the set in two at `key`, returning everything greater than or equal to it.
+    pub fn split_off<Q: ?Sized + Comparable<K>>(&mut self, key: &Q) -> Self {
+        let at = self.rank(key);
+        Self { keys: self.keys.split_off(at) }
+    }
+
+    /// Adds all keys of `other` with a single merge pass, in O(n + m).
+    pub fn union_with(&mut self, other: &Self)
+    where
+        K: Clone,
+    {
+        let keys = mem::take(&mut self.keys);
+        *self = Self::combine(keys.into_iter(), other.iter().cloned(), [true, true, true]);
+    }
+
+    /// Keeps only the keys that are also in `other`, in place and in O(n + m).
+    pub fn intersect_with(&mut self, other: &Self) {
+        let mut theirs = other.iter().peekable();
+        self.retain(|key| {
+            while theirs.next_if(|k| *k < key).is_some() {}
+            theirs.peek() == Some(&key)
+        });
+    }
+
+    /// Removes all keys that are in `other`, in place and in O(n + m).
+    pub fn difference_with(&mut self, other: &Self) {
+        let mut theirs = other.iter().peekable();
+        self.retain(|key| {
+            while theirs.next_if(|k| *k < key).is_some() {}
+            theirs.peek() != Some(&key)
+        });
+    }
+
+    /// Removes and returns the smallest key, in O(n).
+    pub fn pop_first(&mut self) -> Option<K> {
+        if self.keys.is_empty() {
+            return None;
+        }
+        Some(self.keys.remove(0))
+    }
+
+    /// Removes and returns the largest key, in O(1).
+    pub fn pop_last(&mut self) -> Option<K> {
+        self.keys.pop()
+    }
+
+    // positional access
+
+    /// Returns the number of keys strictly less than `key`.
+    pub fn rank<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> usize {
+        self.keys.partition_point(|k| key.compare(k) == Ordering::Greater)
+    }
+
+    /// Returns the key with exactly `rank` smaller keys, the inverse of [`FlatSet::rank`].
+    pub fn select(&self, rank: usize) -> Option<&K> {
+        self.keys.get(rank)
+    }
+
+    pub fn first(&self) -> Option<&K> {
+        self.keys.first()
+    }
+
+    pub fn last(&self) -> Option<&K> {
+        self.keys.last()
+    }
+
+    // ranges
+
+    pub fn range<Q: ?Sized + Comparable<K>>(&self, range: impl RangeBounds<Q>) -> Range<'_, K> {
+        let range = sorted::key_range_indices(&self.keys, range, &NaturalOrder);
+        Range { inner: self.keys[range].iter() }
     }
 
     // misc
 
     pub fn is_empty(&self) -> bool {
-        self.inner.is_empty()
+        self.keys.is_empty()
     }
 
     pub fn clear(&mut self) {
-        self.inner.clear();
+        self.keys.clear();
     }
 
     pub fn len(&self) -> usize {
-        self.inner.len()
+        self.keys.len()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.keys.capacity()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.keys.reserve(additional);
+    }
+
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.keys.reserve_exact(additional);
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.keys.shrink_to_fit();
+    }
+
+    /// Returns the keys as a sorted slice.
+    pub fn as_slice(&self) -> &[K] {
+        &self.keys
+    }
+
+    /// Returns the keys as a sorted vector, without copying.
+    pub fn into_vec(self) -> Vec<K> {
+        self.keys
+    }
+
+    /// Transforms every key, re-sorting and deduplicating the result only if `f` turns out not
+    /// to be strictly increasing.
+    ///
+    /// A strictly increasing `f` is detected in O(n), and the allocation is reused where
+    /// possible.
+    pub fn map<U: Ord>(self, f: impl FnMut(K) -> U) -> FlatSet<U> {
+        let keys = self.keys.into_iter().map(f).collect::<Vec<_>>();
+        match FlatSet::first_unsorted(&keys) {
+            Some(_) => FlatSet::from(keys),
+            None => FlatSet { keys },
+        }
     }
 
     // iterators
 
-    pub fn iter(&self) -> impl Iterator<Item=&K> {
-        // BTreeSet::inser
-        self.inner.keys()
+    pub fn iter(&self) -> Iter<'_, K> {
+        Iter { inner: self.keys.iter() }
+    }
+
+    /// Iterates from the first key greater than or equal to `key` to the end of the set.
+    pub fn iter_from<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Iter<'_, K> {
+        Iter { inner: self.keys[self.rank(key)..].iter() }
+    }
+
+    /// Removes all keys, yielding them in order.
+    pub fn drain(&mut self) -> vec::Drain<'_, K> {
+        self.keys.drain(..)
+    }
+
+    /// Lazily removes and yields the keys for which `pred` returns `true`.
+    ///
+    /// Keys not yet visited when the iterator is dropped are kept.
+    pub fn extract_if<'a, F>(&'a mut self, mut pred: F) -> impl Iterator<Item=K> + 'a
+    where
+        F: FnMut(&K) -> bool + 'a,
+    {
+        self.keys.extract_if(.., move |k| pred(k))
+    }
+
+    // internals
+
+    fn search<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Result<usize, usize> {
+        self.keys.binary_search_by(|probe| key.compare(probe).reverse())
+    }
+
+    fn debug_check_invariants(&self) {
+        if cfg!(feature = "debug-invariants") {
+            if let Some(index) = Self::first_unsorted(&self.keys) {
+                panic!("FlatSet invariants violated: {}", NotSortedError { index });
+            }
+        }
+    }
+
+    /// Returns the index of the first key that is not greater than its predecessor.
+    fn first_unsorted(keys: &[K]) -> Option<usize> {
+        keys.windows(2).position(|w| w[0] >= w[1]).map(|i| i + 1)
+    }
+
+    /// Looks up `keys` in sorted order and returns the first one whose presence equals `present`.
+    fn probe<'a>(&self, keys: impl IntoIterator<Item=&'a K>, present: bool) -> Option<&'a K> {
+        let mut keys = keys.into_iter().collect::<Vec<_>>();
+        keys.sort_unstable();
+
+        let mut start = 0;
+        keys.into_iter().find(|&key| {
+            start += sorted::key_gallop(&self.keys[start..], key, &NaturalOrder);
+            self.keys.get(start).is_some_and(|k| k == key) == present
+        })
+    }
+
+    /// Merges two sorted streams of keys, keeping keys only in `left`, in both, and only in
+    /// `right` as selected by `keep`.
+    ///
+    /// On equal keys the key from `left` is kept.
+    fn combine(left: impl Iterator<Item=K>, right: impl Iterator<Item=K>, keep: [bool; 3]) -> Self {
+        Self { keys: Merge::new(left, right, keep).collect() }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::ops::Bound;
+
+    #[test]
+    fn test_traits() {
+        use std::hash::BuildHasher;
+
+        let m = FlatSet::from([2, 1]);
+        assert_eq!(m.clone(), FlatSet::from([1, 2]));
+        assert_eq!(format!("{m:?}"), "{1, 2}");
+        assert!(m < FlatSet::from([1, 3]));
+        assert_eq!(FlatSet::<i32>::default(), FlatSet::new());
+
+        let state = std::hash::RandomState::new();
+        assert_eq!(state.hash_one(&m), state.hash_one(FlatSet::from([1, 2, 1])));
+    }
 
     #[test]
     fn test_contains() {
-        let mut m = FlatSet::from([1, 2, 3]);
+        let m = FlatSet::from([1, 2, 3]);
         assert!(m.contains(&1));
         assert!(m.contains(&2));
         assert!(m.contains(&3));
@@ -91,6 +639,114 @@ mod tests {
         assert!(!m.contains(&100));
     }
 
+    #[test]
+    fn test_floor_ceiling() {
+        let m = FlatSet::from([10, 20, 30]);
+        assert_eq!(m.get_floor(&5), None);
+        assert_eq!(m.get_floor(&20), Some(&20));
+        assert_eq!(m.get_floor(&25), Some(&20));
+        assert_eq!(m.get_floor(&99), Some(&30));
+        assert_eq!(m.get_ceiling(&5), Some(&10));
+        assert_eq!(m.get_ceiling(&20), Some(&20));
+        assert_eq!(m.get_ceiling(&25), Some(&30));
+        assert_eq!(m.get_ceiling(&31), None);
+    }
+
+    #[test]
+    fn test_contains_all_any() {
+        let m = (0..100).step_by(3).collect::<FlatSet<_>>();
+        assert!(m.contains_all(&[99, 0, 33, 33]));
+        assert!(!m.contains_all(&[99, 1, 33]));
+        assert!(m.contains_all(&[]));
+        assert!(m.contains_any(&[1, 2, 96]));
+        assert!(!m.contains_any(&[1, 2, 100]));
+        assert!(!m.contains_any(&[]));
+    }
+
+    #[test]
+    fn test_relations() {
+        let a = FlatSet::from([1, 3, 5]);
+        let b = FlatSet::from([1, 2, 3, 4, 5]);
+        let c = FlatSet::from([2, 4, 6]);
+        assert!(a.is_subset(&b));
+        assert!(!b.is_subset(&a));
+        assert!(b.is_superset(&a));
+        assert!(!a.is_subset(&c));
+        assert!(FlatSet::new().is_subset(&a));
+        assert!(a.is_disjoint(&c));
+        assert!(!b.is_disjoint(&c));
+        assert!(a.is_disjoint(&FlatSet::new()));
+    }
+
+    #[test]
+    fn test_intersection_union_len() {
+        let a = (0..20).step_by(2).collect::<FlatSet<_>>();
+        let b = (0..20).step_by(3).collect::<FlatSet<_>>();
+        assert_eq!(a.intersection_len(&b), (&a & &b).len());
+        assert_eq!(a.union_len(&b), (&a | &b).len());
+        assert_eq!(a.intersection_len(&a), a.len());
+        assert_eq!(a.union_len(&FlatSet::new()), a.len());
+        assert_eq!(FlatSet::<i32>::new().intersection_len(&b), 0);
+    }
+
+    #[test]
+    fn test_operators() {
+        let a = FlatSet::from([1, 2, 3, 4]);
+        let b = FlatSet::from([3, 4, 5]);
+        let keys = |s: FlatSet<i32>| s.iter().copied().collect::<Vec<_>>();
+        assert_eq!(keys(&a | &b), vec![1, 2, 3, 4, 5]);
+        assert_eq!(keys(&a & &b), vec![3, 4]);
+        assert_eq!(keys(&a - &b), vec![1, 2]);
+        assert_eq!(keys(&a ^ &b), vec![1, 2, 5]);
+
+        let mut c = FlatSet::from([1, 5]);
+        c |= &b;
+        assert_eq!(keys(c), vec![1, 3, 4, 5]);
+        let mut c = a;
+        c -= &b;
+        c ^= &FlatSet::from([2, 9]);
+        c &= &FlatSet::from([1, 9]);
+        assert_eq!(keys(c), vec![1, 9]);
+    }
+
+    #[test]
+    fn test_union_intersection_all() {
+        let a = (0..30).step_by(2).collect::<FlatSet<_>>();
+        let b = (0..30).step_by(3).collect::<FlatSet<_>>();
+        let c = FlatSet::from([0, 6, 12, 13, 24, 100]);
+        assert_eq!(FlatSet::union_all(&[&a, &b, &c]), &(&a | &b) | &c);
+        assert_eq!(FlatSet::intersection_all(&[&a, &b, &c]).as_slice(), &[0, 6, 12, 24]);
+        assert_eq!(FlatSet::intersection_all(&[&a, &FlatSet::new()]), FlatSet::new());
+        assert_eq!(FlatSet::intersection_all(&[&a]), a);
+        assert_eq!(FlatSet::<i32>::intersection_all(&[]), FlatSet::new());
+        assert_eq!(FlatSet::<i32>::union_all(&[]), FlatSet::new());
+    }
+
+    #[test]
+    fn test_borrowed() {
+        let mut m = FlatSet::from(["a".to_string(), "b".to_string()]);
+        assert!(m.contains("a"));
+        assert_eq!(m.get("b"), Some(&"b".to_string()));
+        assert!(m.remove("a"));
+        assert!(!m.contains("a"));
+    }
+
+    #[test]
+    fn test_in_place() {
+        let keys = |s: &FlatSet<i32>| s.iter().copied().collect::<Vec<_>>();
+        let candidates = FlatSet::from([2, 3, 5, 7, 11]);
+
+        let mut m = (0..10).collect::<FlatSet<_>>();
+        m.intersect_with(&candidates);
+        assert_eq!(keys(&m), vec![2, 3, 5, 7]);
+        m.difference_with(&FlatSet::from([1, 3, 7, 20]));
+        assert_eq!(keys(&m), vec![2, 5]);
+        m.union_with(&FlatSet::from([0, 5, 9]));
+        assert_eq!(keys(&m), vec![0, 2, 5, 9]);
+        m.intersect_with(&FlatSet::new());
+        assert!(m.is_empty());
+    }
+
     #[test]
     fn test_insert() {
         let mut m = FlatSet::from([1, 2, 3]);
@@ -98,11 +754,76 @@ mod tests {
         assert!(!m.insert(4));
     }
 
+    #[test]
+    fn test_canonicalize() {
+        use std::sync::Arc;
+
+        let mut m = FlatSet::new();
+        let first = Arc::<str>::from("key");
+        assert_eq!(m.replace(Arc::clone(&first)), None);
+        assert!(Arc::ptr_eq(m.get("key").unwrap(), &first));
+
+        let second = Arc::<str>::from("key");
+        assert!(Arc::ptr_eq(&m.replace(Arc::clone(&second)).unwrap(), &first));
+        assert!(Arc::ptr_eq(m.get("key").unwrap(), &second));
+        assert!(Arc::ptr_eq(&m.take("key").unwrap(), &second));
+        assert!(m.get("key").is_none());
+        assert!(m.take("key").is_none());
+    }
+
+    #[test]
+    fn test_get_or_insert() {
+        use std::sync::Arc;
+
+        let mut m = FlatSet::<Arc<str>>::new();
+        let first = Arc::clone(m.get_or_insert_with("b", |k| Arc::from(k)));
+        assert!(Arc::ptr_eq(m.get_or_insert_with("b", |_| unreachable!()), &first));
+        assert!(Arc::ptr_eq(m.get_or_insert(Arc::from("b")), &first));
+        assert_eq!(&**m.get_or_insert(Arc::from("a")), "a");
+        assert_eq!(m.len(), 2);
+    }
+
     #[test]
     fn test_remove() {
         let mut m = FlatSet::from([1, 2, 3]);
-        m.remove(&2);
+        assert!(m.remove(&2));
         assert!(!m.contains(&2));
+        assert!(!m.remove(&2));
+    }
+
+    #[test]
+    fn test_rank_select() {
+        let m = FlatSet::from([10, 20, 30, 40]);
+        assert_eq!(m.rank(&5), 0);
+        assert_eq!(m.rank(&20), 1);
+        assert_eq!(m.rank(&25), 2);
+        assert_eq!(m.rank(&99), 4);
+        assert_eq!(m.select(2), Some(&30));
+        assert_eq!(m.select(4), None);
+        assert_eq!(m.select(m.rank(&40)), Some(&40));
+    }
+
+    #[test]
+    fn test_first_last() {
+        let mut m = FlatSet::from([3, 1, 2]);
+        assert_eq!(m.first(), Some(&1));
+        assert_eq!(m.last(), Some(&3));
+        assert_eq!(m.pop_last(), Some(3));
+        assert_eq!(m.pop_first(), Some(1));
+        assert_eq!(m.pop_first(), Some(2));
+        assert_eq!(m.pop_last(), None);
+        assert_eq!(m.first(), None);
+        assert_eq!(m.last(), None);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut m = (0..10).collect::<FlatSet<_>>();
+        m.retain(|k| k % 3 != 0);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), vec![1, 2, 4, 5, 7, 8]);
+        assert_eq!(m.extract_if(|k| k % 2 == 0).collect::<Vec<_>>(), vec![2, 4, 8]);
+        assert_eq!(m.drain().rev().collect::<Vec<_>>(), vec![7, 5, 1]);
+        assert!(m.is_empty());
     }
 
     #[test]
@@ -119,9 +840,109 @@ mod tests {
         assert_eq!(m.len(), 3);
     }
 
+    #[test]
+    fn test_capacity() {
+        let mut m = FlatSet::with_capacity(10);
+        assert!(m.capacity() >= 10);
+        m.insert(1);
+        m.reserve(20);
+        assert!(m.capacity() >= 21);
+        m.reserve_exact(30);
+        assert!(m.capacity() >= 31);
+        m.shrink_to_fit();
+        assert!(m.contains(&1));
+    }
+
+    #[test]
+    fn test_as_slice() {
+        let mut m = FlatSet::from([3, 1, 2, 1]);
+        assert_eq!(m.as_slice(), &[1, 2, 3]);
+        m.insert(0);
+        assert_eq!(m.as_slice().binary_search(&2), Ok(2));
+        assert_eq!(m.into_vec(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_map() {
+        let m = FlatSet::from([1, 2, 3]);
+        assert_eq!(m.clone().map(|k| k * 10).as_slice(), &[10, 20, 30]);
+        assert_eq!(m.clone().map(|k| -k).as_slice(), &[-3, -2, -1]);
+        assert_eq!(m.map(|k| k / 2).as_slice(), &[0, 1]);
+    }
+
     #[test]
     fn test_iter() {
         let m = FlatSet::from([1, 2, 3]);
         assert_eq!(m.iter().count(), 3);
+        assert_eq!(m.iter().rev().collect::<Vec<_>>(), vec![&3, &2, &1]);
+        assert_eq!((&m).into_iter().len(), 3);
+    }
+
+    #[test]
+    fn test_iter_from() {
+        let m = FlatSet::from([1, 3, 5]);
+        assert_eq!(m.iter_from(&2).collect::<Vec<_>>(), vec![&3, &5]);
+        assert_eq!(m.iter_from(&3).len(), 2);
+        assert_eq!(m.iter_from(&6).next(), None);
+    }
+
+    #[test]
+    fn test_split_off_append() {
+        let mut m = (0..10).collect::<FlatSet<_>>();
+        let mut upper = m.split_off(&6);
+        assert_eq!(m.len(), 6);
+        assert_eq!(upper.iter().copied().collect::<Vec<_>>(), vec![6, 7, 8, 9]);
+
+        upper.insert(3);
+        m.append(&mut upper);
+        assert!(upper.is_empty());
+        assert_eq!(m, (0..10).collect());
+    }
+
+    #[test]
+    fn test_from_sorted() {
+        let m = FlatSet::from_sorted_vec_unchecked(vec![1, 2, 5]);
+        assert!(m.contains(&5));
+        assert_eq!(FlatSet::try_from_sorted(vec![1, 2, 5]), Ok(m));
+        assert_eq!(FlatSet::try_from_sorted(vec![1, 3, 3]).unwrap_err().index(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "FlatSet invariants violated")]
+    #[cfg(feature = "debug-invariants")]
+    fn test_from_sorted_vec_unchecked_debug_invariants() {
+        FlatSet::from_sorted_vec_unchecked(vec![3, 1]);
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut m = FlatSet::from([5, 1]);
+        m.extend([4, 2, 4, 1]);
+        m.extend(&[0]);
+        assert_eq!(m.into_iter().collect::<Vec<_>>(), vec![0, 1, 2, 4, 5]);
+        assert_eq!(FlatSet::from([2, 1]).into_iter().rev().len(), 2);
+    }
+
+    #[test]
+    fn test_remove_range() {
+        let mut m = (0..10).collect::<FlatSet<_>>();
+        assert_eq!(m.remove_range(3..6).collect::<Vec<_>>(), vec![3, 4, 5]);
+        assert_eq!(m.remove_range(8..).len(), 2);
+        assert_eq!(m.remove_range(20..30).len(), 0);
+        assert_eq!(m.as_slice(), &[0, 1, 2, 6, 7]);
+
+        m.remove_range(..=1);
+        assert_eq!(m.as_slice(), &[2, 6, 7]);
+    }
+
+    #[test]
+    fn test_range() {
+        let m = FlatSet::from([1, 3, 5, 7, 9]);
+        assert_eq!(m.range(2..8).collect::<Vec<_>>(), vec![&3, &5, &7]);
+        assert_eq!(m.range(..=3).rev().collect::<Vec<_>>(), vec![&3, &1]);
+        assert_eq!(m.range(6..).len(), 2);
+
+        let m = FlatSet::from(["a".to_string(), "b".to_string()]);
+        assert_eq!(m.range::<str>((Bound::Excluded("a"), Bound::Unbounded)).count(), 1);
     }
 }
@@ -0,0 +1,87 @@
+use super::{FlatSet, Merge};
+
+/// A lazily evaluated union, intersection or difference of borrowed [`FlatSet`]s.
+///
+/// The whole expression is evaluated in a single fused merge pass over its operands, without
+/// building intermediate sets.
+pub struct SetExpr<'a, K: Ord> {
+    node: Node<'a, K>,
+}
+
+enum Node<'a, K: Ord> {
+    Set(&'a FlatSet<K>),
+    Op(Box<SetExpr<'a, K>>, Box<SetExpr<'a, K>>, [bool; 3]),
+}
+
+impl<'a, K: Ord> From<&'a FlatSet<K>> for SetExpr<'a, K> {
+    fn from(set: &'a FlatSet<K>) -> Self {
+        Self::new(set)
+    }
+}
+
+impl<'a, K: Ord> SetExpr<'a, K> {
+    pub fn new(set: &'a FlatSet<K>) -> Self {
+        Self { node: Node::Set(set) }
+    }
+
+    pub fn union(self, other: impl Into<Self>) -> Self {
+        self.op(other.into(), [true, true, true])
+    }
+
+    pub fn intersect(self, other: impl Into<Self>) -> Self {
+        self.op(other.into(), [false, true, false])
+    }
+
+    pub fn difference(self, other: impl Into<Self>) -> Self {
+        self.op(other.into(), [true, false, false])
+    }
+
+    pub fn symmetric_difference(self, other: impl Into<Self>) -> Self {
+        self.op(other.into(), [true, false, true])
+    }
+
+    /// Streams the keys of the result in order.
+    pub fn iter(&self) -> impl Iterator<Item=&'a K> + '_ {
+        self.stream()
+    }
+
+    /// Collects the result into a new set.
+    pub fn evaluate(&self) -> FlatSet<K>
+    where
+        K: Clone,
+    {
+        FlatSet { keys: self.iter().cloned().collect() }
+    }
+
+    fn op(self, other: Self, keep: [bool; 3]) -> Self {
+        Self { node: Node::Op(Box::new(self), Box::new(other), keep) }
+    }
+
+    fn stream(&self) -> Box<dyn Iterator<Item=&'a K> + '_> {
+        match &self.node {
+            Node::Set(set) => Box::new(set.iter()),
+            Node::Op(left, right, keep) => Box::new(Merge::new(left.stream(), right.stream(), *keep)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate() {
+        let a = (0..20).collect::<FlatSet<_>>();
+        let b = (10..30).collect::<FlatSet<_>>();
+        let c = (0..30).step_by(3).collect::<FlatSet<_>>();
+        let d = FlatSet::from([12, 40]);
+
+        let expr = SetExpr::from(&a).intersect(&b).union(&c).difference(&d);
+        assert_eq!(expr.evaluate(), &(&(&a & &b) | &c) - &d);
+        assert_eq!(expr.iter().count(), expr.evaluate().len());
+
+        let nested = SetExpr::new(&a).symmetric_difference(SetExpr::new(&b).intersect(&c));
+        assert_eq!(nested.evaluate(), &a ^ &(&b & &c));
+        assert_eq!(SetExpr::new(&d).intersect(&a).evaluate().as_slice(), &[12]);
+    }
+}
@@ -0,0 +1,126 @@
+use std::{cmp::Ordering, iter::{FusedIterator, Peekable}, slice, vec};
+
+/// An iterator over the keys of a [`FlatSet`](crate::FlatSet) in order.
+#[derive(Clone)]
+pub struct Iter<'a, K> {
+    pub(super) inner: slice::Iter<'a, K>,
+}
+
+impl<'a, K> Iterator for Iter<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K> DoubleEndedIterator for Iter<'_, K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<K> ExactSizeIterator for Iter<'_, K> {}
+
+impl<K> FusedIterator for Iter<'_, K> {}
+
+/// An iterator over a range of keys of a [`FlatSet`](crate::FlatSet) in order.
+#[derive(Clone)]
+pub struct Range<'a, K> {
+    pub(super) inner: slice::Iter<'a, K>,
+}
+
+impl<'a, K> Iterator for Range<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K> DoubleEndedIterator for Range<'_, K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<K> ExactSizeIterator for Range<'_, K> {}
+
+impl<K> FusedIterator for Range<'_, K> {}
+
+/// An owning iterator over the keys of a [`FlatSet`](crate::FlatSet) in order.
+pub struct IntoIter<K> {
+    pub(super) inner: vec::IntoIter<K>,
+}
+
+impl<K> Iterator for IntoIter<K> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K> DoubleEndedIterator for IntoIter<K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<K> ExactSizeIterator for IntoIter<K> {}
+
+impl<K> FusedIterator for IntoIter<K> {}
+
+/// Merges two sorted streams of keys, yielding keys only in `left`, in both, and only in
+/// `right` as selected by `keep`.
+///
+/// On equal keys the key from `left` is yielded.
+pub(super) struct Merge<L: Iterator, R: Iterator> {
+    left: Peekable<L>,
+    right: Peekable<R>,
+    keep: [bool; 3],
+}
+
+impl<L: Iterator, R: Iterator> Merge<L, R> {
+    pub(super) fn new(left: L, right: R, keep: [bool; 3]) -> Self {
+        Self { left: left.peekable(), right: right.peekable(), keep }
+    }
+}
+
+impl<K: Ord, L: Iterator<Item=K>, R: Iterator<Item=K>> Iterator for Merge<L, R> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let ordering = match (self.left.peek(), self.right.peek()) {
+                (Some(l), Some(r)) => l.cmp(r),
+                (Some(_), None) if self.keep[0] => return self.left.next(),
+                (None, Some(_)) if self.keep[2] => return self.right.next(),
+                _ => return None,
+            };
+            let key = match ordering {
+                Ordering::Less => self.left.next().filter(|_| self.keep[0]),
+                Ordering::Equal => {
+                    self.right.next();
+                    self.left.next().filter(|_| self.keep[1])
+                }
+                Ordering::Greater => self.right.next().filter(|_| self.keep[2]),
+            };
+            if key.is_some() {
+                return key;
+            }
+        }
+    }
+}
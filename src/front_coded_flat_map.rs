@@ -0,0 +1,287 @@
+use crate::sorted;
+use std::{cmp::Ordering, fmt, marker::PhantomData, slice};
+
+/// Number of keys per bucket. Each bucket starts with a key stored in full, the other keys
+/// only store the suffix that differs from their predecessor.
+const BUCKET_LEN: usize = 16;
+
+/// Key types that can be stored as byte strings in a [`FrontCodedFlatMap`].
+///
+/// Byte order coincides with [`Ord`] for both implementors, so the map iterates in the
+/// usual key order.
+pub trait ByteKey: Ord {
+    /// Type returned when reconstructing a key.
+    type Owned;
+
+    fn as_bytes(&self) -> &[u8];
+
+    /// Reconstructs a key from bytes that were produced by [`ByteKey::as_bytes`].
+    fn from_bytes(bytes: Vec<u8>) -> Self::Owned;
+}
+
+impl ByteKey for str {
+    type Owned = String;
+
+    fn as_bytes(&self) -> &[u8] {
+        str::as_bytes(self)
+    }
+
+    fn from_bytes(bytes: Vec<u8>) -> String {
+        String::from_utf8(bytes).expect("stored keys are valid UTF-8")
+    }
+}
+
+impl ByteKey for [u8] {
+    type Owned = Vec<u8>;
+
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn from_bytes(bytes: Vec<u8>) -> Vec<u8> {
+        bytes
+    }
+}
+
+/// Immutable map with string or byte string keys stored with front coding.
+///
+/// Keys are grouped in buckets of 16, and every key but the first of a bucket only stores
+/// the part that differs from the previous key. Sets of keys with long shared prefixes,
+/// like hierarchical paths, take a fraction of the memory of a [`FrozenFlatMap`](crate::FrozenFlatMap).
+/// Lookups binary search the bucket heads and then decode at most one bucket.
+pub struct FrontCodedFlatMap<K: ?Sized + ByteKey, V> {
+    bytes: Box<[u8]>,
+    buckets: Box<[usize]>,
+    values: Box<[V]>,
+    marker: PhantomData<fn() -> K::Owned>,
+}
+
+impl<K: ?Sized + ByteKey, V: Clone> Clone for FrontCodedFlatMap<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            bytes: self.bytes.clone(),
+            buckets: self.buckets.clone(),
+            values: self.values.clone(),
+            marker: self.marker,
+        }
+    }
+}
+
+impl<K: ?Sized + ByteKey, V: fmt::Debug> fmt::Debug for FrontCodedFlatMap<K, V>
+where
+    K::Owned: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: ?Sized + ByteKey, V> Default for FrontCodedFlatMap<K, V> {
+    fn default() -> Self {
+        Self { bytes: Box::new([]), buckets: Box::new([]), values: Box::new([]), marker: PhantomData }
+    }
+}
+
+/// If there are duplicates, the last one is kept.
+impl<K: ?Sized + ByteKey, V, T: AsRef<K>> From<Vec<(T, V)>> for FrontCodedFlatMap<K, V> {
+    fn from(mut items: Vec<(T, V)>) -> Self {
+        sorted::sort_dedup(&mut items, &|a: &T, b: &T| a.as_ref().as_bytes().cmp(b.as_ref().as_bytes()));
+
+        let mut bytes = Vec::new();
+        let mut buckets = Vec::with_capacity(items.len().div_ceil(BUCKET_LEN));
+        let mut values = Vec::with_capacity(items.len());
+        let mut prev: &[u8] = &[];
+        for (i, (key, _)) in items.iter().enumerate() {
+            let key = key.as_ref().as_bytes();
+            let prefix = if i % BUCKET_LEN == 0 {
+                buckets.push(bytes.len());
+                0
+            } else {
+                prev.iter().zip(key).take_while(|(a, b)| a == b).count()
+            };
+            write_varint(&mut bytes, prefix);
+            write_varint(&mut bytes, key.len() - prefix);
+            bytes.extend_from_slice(&key[prefix..]);
+            prev = key;
+        }
+        values.extend(items.into_iter().map(|(_, v)| v));
+
+        Self {
+            bytes: bytes.into_boxed_slice(),
+            buckets: buckets.into_boxed_slice(),
+            values: values.into_boxed_slice(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<K: ?Sized + ByteKey, V, T: AsRef<K>, const N: usize> From<[(T, V); N]> for FrontCodedFlatMap<K, V> {
+    fn from(value: [(T, V); N]) -> Self {
+        Self::from(Vec::from(value))
+    }
+}
+
+impl<K: ?Sized + ByteKey, V, T: AsRef<K>> FromIterator<(T, V)> for FrontCodedFlatMap<K, V> {
+    fn from_iter<I: IntoIterator<Item=(T, V)>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<K: ?Sized + ByteKey, V> FrontCodedFlatMap<K, V> {
+    // lookup
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.index_of(key).is_some()
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.index_of(key).map(|i| &self.values[i])
+    }
+
+    // positional access
+
+    /// Returns the entry at position `index` in key order, decoding its key.
+    pub fn get_index(&self, index: usize) -> Option<(K::Owned, &V)> {
+        let value = self.values.get(index)?;
+        let bucket = index / BUCKET_LEN;
+        let mut key = Vec::new();
+        let mut pos = self.buckets[bucket];
+        for _ in bucket * BUCKET_LEN..=index {
+            pos = self.decode_next(pos, &mut key);
+        }
+        Some((K::from_bytes(key), value))
+    }
+
+    /// Returns the position of `key` in key order.
+    pub fn index_of(&self, key: &K) -> Option<usize> {
+        let key = key.as_bytes();
+        let bucket = self.buckets.partition_point(|&pos| self.head(pos) <= key).checked_sub(1)?;
+
+        let mut current = Vec::new();
+        let mut pos = self.buckets[bucket];
+        let end = self.values.len().min((bucket + 1) * BUCKET_LEN);
+        for index in bucket * BUCKET_LEN..end {
+            pos = self.decode_next(pos, &mut current);
+            match current.as_slice().cmp(key) {
+                Ordering::Less => {}
+                Ordering::Equal => return Some(index),
+                Ordering::Greater => return None,
+            }
+        }
+        None
+    }
+
+    // misc
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns the number of bytes used to store the encoded keys.
+    pub fn key_bytes(&self) -> usize {
+        self.bytes.len() + self.buckets.len() * size_of::<usize>()
+    }
+
+    /// Returns all values as a slice, in key order.
+    pub fn values_as_slice(&self) -> &[V] {
+        &self.values
+    }
+
+    // iterators
+
+    /// Iterates over the entries in key order, decoding every key.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item=(K::Owned, &V)> {
+        let mut key = Vec::new();
+        let mut pos = 0;
+        self.values.iter().map(move |value| {
+            pos = self.decode_next(pos, &mut key);
+            (K::from_bytes(key.clone()), value)
+        })
+    }
+
+    pub fn keys(&self) -> impl ExactSizeIterator<Item=K::Owned> + '_ {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> slice::Iter<'_, V> {
+        self.values.iter()
+    }
+
+    // internals
+
+    /// Returns the full key that starts a bucket at byte offset `pos`.
+    fn head(&self, pos: usize) -> &[u8] {
+        let (_, pos) = read_varint(&self.bytes, pos);
+        let (len, pos) = read_varint(&self.bytes, pos);
+        &self.bytes[pos..pos + len]
+    }
+
+    /// Decodes the key at byte offset `pos` on top of its predecessor in `key`, returning
+    /// the offset of the next key.
+    fn decode_next(&self, pos: usize, key: &mut Vec<u8>) -> usize {
+        let (prefix, pos) = read_varint(&self.bytes, pos);
+        let (len, pos) = read_varint(&self.bytes, pos);
+        key.truncate(prefix);
+        key.extend_from_slice(&self.bytes[pos..pos + len]);
+        pos + len
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut n: usize) {
+    while n >= 0x80 {
+        bytes.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+}
+
+fn read_varint(bytes: &[u8], mut pos: usize) -> (usize, usize) {
+    let mut n = 0;
+    let mut shift = 0;
+    loop {
+        let byte = bytes[pos];
+        pos += 1;
+        n |= ((byte & 0x7f) as usize) << shift;
+        if byte < 0x80 {
+            return (n, pos);
+        }
+        shift += 7;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths() -> Vec<(String, usize)> {
+        (0..100).map(|i| (format!("/usr/share/doc/package-{:03}/README", i), i)).collect()
+    }
+
+    #[test]
+    fn test_get() {
+        let m = paths().into_iter().rev().collect::<FrontCodedFlatMap<str, _>>();
+        assert_eq!(m.len(), 100);
+        for (i, (key, value)) in paths().iter().enumerate() {
+            assert_eq!(m.get(key), Some(value));
+            assert_eq!(m.index_of(key), Some(i));
+            assert_eq!(m.get_index(i), Some((key.clone(), value)));
+        }
+        assert_eq!(m.get(""), None);
+        assert_eq!(m.get("/usr/share/doc/package-050"), None);
+        assert_eq!(m.get("/usr/share/doc/package-100/README"), None);
+        assert_eq!(m.get_index(100), None);
+        assert!(m.key_bytes() < paths().iter().map(|(k, _)| k.len()).sum::<usize>() / 2);
+    }
+
+    #[test]
+    fn test_iter() {
+        let m = FrontCodedFlatMap::<[u8], _>::from([(&b"ab"[..], 1), (b"a", 2), (b"", 3), (b"ab", 4)]);
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(vec![], &3), (b"a".to_vec(), &2), (b"ab".to_vec(), &4)]);
+        assert_eq!(m.values_as_slice(), &[3, 2, 4]);
+        assert!(FrontCodedFlatMap::<str, ()>::default().is_empty());
+    }
+}
@@ -0,0 +1,462 @@
+use crate::{flat_map::{IntoIter, Iter, Keys, Values}, Comparable, Compare, FlatMap, FlatMapView, NaturalOrder, NotSortedError};
+use std::{cmp::Reverse, collections::BinaryHeap, fmt, hash::{Hash, Hasher}, ops::RangeBounds};
+
+type Inner<K, V, C> = FlatMap<K, V, C, Box<[(K, V)]>>;
+
+/// Immutable map backed by a boxed slice sorted by key.
+///
+/// Has no spare capacity and no mutating operations. Use [`FlatMap::freeze`]
+/// and [`FrozenFlatMap::thaw`] to switch between the two without re-sorting.
+///
+/// Asymptotics:
+///
+/// | operation | average | worst   | best    |
+/// |-----------|---------|---------|---------|
+/// | lookup    | O(logn) | O(logn) | O(logn) |
+///
+/// Like [`FlatMap`], keys are ordered by the comparator `C`, which also selects the search
+/// algorithm, e.g. `FrozenFlatMap<u64, V, Interpolation>` for evenly spread integer keys.
+pub struct FrozenFlatMap<K: Ord, V, C = NaturalOrder> {
+    inner: Inner<K, V, C>,
+}
+
+impl<K: Ord + Clone, V: Clone, C: Clone> Clone for FrozenFlatMap<K, V, C> {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone() }
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug, C> fmt::Debug for FrozenFlatMap<K, V, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl<K: Ord, V, C: Default> Default for FrozenFlatMap<K, V, C> {
+    fn default() -> Self {
+        Self { inner: FlatMap::default() }
+    }
+}
+
+/// Maps are compared by their entries only.
+impl<K: Ord, V: PartialEq, C> PartialEq for FrozenFlatMap<K, V, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<K: Ord, V: Eq, C> Eq for FrozenFlatMap<K, V, C> {}
+
+impl<K: Ord + Hash, V: Hash, C> Hash for FrozenFlatMap<K, V, C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner.hash(state);
+    }
+}
+
+/// If there are duplicates, the last one is kept.
+impl<K: Ord, V> From<Vec<(K, V)>> for FrozenFlatMap<K, V> {
+    fn from(items: Vec<(K, V)>) -> Self {
+        Self::from(FlatMap::from(items))
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> From<&[(K, V)]> for FrozenFlatMap<K, V> {
+    fn from(value: &[(K, V)]) -> Self {
+        Self::from(value.to_vec())
+    }
+}
+
+impl<K: Ord + Clone, V: Clone, const N: usize> From<[(K, V); N]> for FrozenFlatMap<K, V> {
+    fn from(value: [(K, V); N]) -> Self {
+        Self::from(value.to_vec())
+    }
+}
+
+impl<K: Ord, V, C: Compare<K> + Default> FromIterator<(K, V)> for FrozenFlatMap<K, V, C> {
+    fn from_iter<I: IntoIterator<Item=(K, V)>>(iter: I) -> Self {
+        Self::from(FlatMap::from_iter(iter))
+    }
+}
+
+impl<K: Ord, V, C> From<FlatMap<K, V, C>> for FrozenFlatMap<K, V, C> {
+    fn from(map: FlatMap<K, V, C>) -> Self {
+        let (items, cmp) = map.into_parts();
+        Self { inner: FlatMap::from_parts(items.into_boxed_slice(), cmp) }
+    }
+}
+
+impl<K: Ord, V, C> From<FrozenFlatMap<K, V, C>> for FlatMap<K, V, C> {
+    fn from(map: FrozenFlatMap<K, V, C>) -> Self {
+        let (items, cmp) = map.inner.into_parts();
+        FlatMap::from_parts(items.into_vec(), cmp)
+    }
+}
+
+impl<K: Ord, V, C> IntoIterator for FrozenFlatMap<K, V, C> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        FlatMap::from(self).into_iter()
+    }
+}
+
+impl<'a, K: Ord, V, C> IntoIterator for &'a FrozenFlatMap<K, V, C> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        (&self.inner).into_iter()
+    }
+}
+
+impl<K: Ord, V, C: Compare<K>> AsRef<[(K, V)]> for FrozenFlatMap<K, V, C> {
+    fn as_ref(&self) -> &[(K, V)] {
+        self.as_slice()
+    }
+}
+
+impl<K: Ord, V> FrozenFlatMap<K, V> {
+    /// Wraps entries that are already sorted by key and free of duplicates, without checking them.
+    ///
+    /// Lookups on a map built from unsorted input return unspecified results.
+    /// The order is verified with the `debug-invariants` feature.
+    pub fn from_sorted_unchecked(items: Box<[(K, V)]>) -> Self {
+        let inner = FlatMap::from_parts(items, NaturalOrder);
+        inner.debug_check_invariants();
+        Self { inner }
+    }
+
+    /// Wraps entries that are already sorted by key and free of duplicates in O(n).
+    pub fn try_from_sorted(items: Box<[(K, V)]>) -> Result<Self, NotSortedError> {
+        FlatMap::try_from_sorted_storage(items, NaturalOrder).map(|inner| Self { inner })
+    }
+
+    /// Merges several maps into one in O(n log k) for `n` entries in `k` maps.
+    ///
+    /// Values of a key present in several maps are folded with `resolve` in the order of
+    /// `maps`, starting from the value in the first of them, e.g. `|_, _, v| v.clone()` lets
+    /// later maps take precedence.
+    pub fn merge<F>(maps: &[&Self], mut resolve: F) -> Self
+    where
+        K: Clone,
+        V: Clone,
+        F: FnMut(&K, V, &V) -> V,
+    {
+        let mut items = Vec::with_capacity(maps.iter().map(|m| m.len()).sum());
+        let mut positions = vec![0; maps.len()];
+        let mut heap = maps
+            .iter()
+            .enumerate()
+            .filter_map(|(i, m)| Some(Reverse((&m.as_slice().first()?.0, i))))
+            .collect::<BinaryHeap<_>>();
+
+        let mut advance = |heap: &mut BinaryHeap<_>, i: usize| {
+            positions[i] += 1;
+            let entries = maps[i].as_slice();
+            if let Some((k, _)) = entries.get(positions[i]) {
+                heap.push(Reverse((k, i)));
+            }
+            &entries[positions[i] - 1].1
+        };
+
+        while let Some(Reverse((key, i))) = heap.pop() {
+            let mut value = advance(&mut heap, i).clone();
+            while let Some(&Reverse((next, j))) = heap.peek() {
+                if next != key {
+                    break;
+                }
+                heap.pop();
+                value = resolve(key, value, advance(&mut heap, j));
+            }
+            items.push((key.clone(), value));
+        }
+        Self::from_sorted_unchecked(items.into_boxed_slice())
+    }
+
+    /// Returns a view of the entries within `range`, in O(log n).
+    ///
+    /// Lookups, ranges and iteration on the view only see keys inside `range`.
+    pub fn slice<Q: ?Sized + Comparable<K>>(&self, range: impl RangeBounds<Q>) -> FlatMapView<'_, K, V> {
+        FlatMapView::from(self).subview(range)
+    }
+}
+
+impl<K: Ord, V, C: Compare<K>> FrozenFlatMap<K, V, C> {
+    /// Converts the map back into a mutable [`FlatMap`] without re-sorting.
+    pub fn thaw(self) -> FlatMap<K, V, C> {
+        FlatMap::from(self)
+    }
+
+    pub fn comparator(&self) -> &C {
+        self.inner.comparator()
+    }
+
+    // lookup
+
+    pub fn contains_key<Q: ?Sized>(&self, key: &Q) -> bool
+    where
+        C: Compare<K, Q>,
+    {
+        self.inner.contains_key(key)
+    }
+
+    pub fn get<Q: ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        C: Compare<K, Q>,
+    {
+        self.inner.get(key)
+    }
+
+    pub fn get_key_value<Q: ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        C: Compare<K, Q>,
+    {
+        self.inner.get_key_value(key)
+    }
+
+    /// Looks up many keys at once with a single galloping pass, see [`FlatMap::get_batch`].
+    pub fn get_batch<Q>(&self, keys: &[Q]) -> Vec<Option<&V>>
+    where
+        C: Compare<K, Q> + Compare<Q>,
+    {
+        self.inner.get_batch(keys)
+    }
+
+    /// Like [`FrozenFlatMap::get_batch`], but skips sorting for `keys` that are already in map order.
+    ///
+    /// Results for keys that are out of order are unspecified.
+    pub fn get_sorted_batch<Q>(&self, keys: &[Q]) -> Vec<Option<&V>>
+    where
+        C: Compare<K, Q>,
+    {
+        self.inner.get_sorted_batch(keys)
+    }
+
+    // positional access
+
+    /// Returns the entry at position `index` in key order.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.inner.get_index(index)
+    }
+
+    /// Returns the position of `key` in key order.
+    pub fn index_of<Q: ?Sized>(&self, key: &Q) -> Option<usize>
+    where
+        C: Compare<K, Q>,
+    {
+        self.inner.index_of(key)
+    }
+
+    /// Returns the number of keys strictly less than `key`.
+    pub fn rank<Q: ?Sized>(&self, key: &Q) -> usize
+    where
+        C: Compare<K, Q>,
+    {
+        self.inner.rank(key)
+    }
+
+    /// Returns the entry with exactly `rank` smaller keys, the inverse of [`FrozenFlatMap::rank`].
+    pub fn select(&self, rank: usize) -> Option<(&K, &V)> {
+        self.inner.get_index(rank)
+    }
+
+    // ranges
+
+    pub fn range<Q: ?Sized>(&self, range: impl RangeBounds<Q>) -> Iter<'_, K, V>
+    where
+        C: Compare<K, Q>,
+    {
+        self.inner.range(range)
+    }
+
+    // misc
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns the entries as a slice sorted by key.
+    pub fn as_slice(&self) -> &[(K, V)] {
+        self.inner.as_slice()
+    }
+
+    /// Transforms every value while keeping the keys and their order, without re-sorting.
+    pub fn map_values<U>(self, f: impl FnMut(&K, V) -> U) -> FrozenFlatMap<K, U, C> {
+        self.thaw().map_values(f).freeze()
+    }
+
+    // iterators
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.inner.iter()
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        self.inner.keys()
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        self.inner.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ops::Bound;
+
+    #[test]
+    fn test_from() {
+        let m = FrozenFlatMap::from([(5, 6), (1, 2), (3, 4), (1, 3)]);
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&1, &3), (&3, &4), (&5, &6)]);
+    }
+
+    #[test]
+    fn test_from_sorted() {
+        let m = FrozenFlatMap::try_from_sorted(Box::new([(1, 2), (3, 4)])).unwrap();
+        assert_eq!(m.get(&3), Some(&4));
+        let err = FrozenFlatMap::try_from_sorted(Box::new([(1, 2), (3, 4), (3, 5)])).unwrap_err();
+        assert_eq!(err.index(), 2);
+        assert_eq!(FrozenFlatMap::from_sorted_unchecked(Box::new([(1, 2)])).len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "FlatMap invariants violated")]
+    #[cfg(feature = "debug-invariants")]
+    fn test_from_sorted_unchecked_debug_invariants() {
+        FrozenFlatMap::from_sorted_unchecked(Box::new([(3, 4), (1, 2)]));
+    }
+
+    #[test]
+    fn test_thaw() {
+        let mut m = FrozenFlatMap::from([(1, 2), (3, 4)]).thaw();
+        m.insert(5, 6);
+        let m = FrozenFlatMap::from(m);
+        assert_eq!(FlatMap::from(m).len(), 3);
+    }
+
+    #[test]
+    fn test_get() {
+        let m = FrozenFlatMap::from([("a".to_string(), 1), ("b".to_string(), 2)]);
+        assert!(m.contains_key("a"));
+        assert!(!m.contains_key("c"));
+        assert_eq!(m.get("b"), Some(&2));
+        assert_eq!(m.get_key_value("a"), Some((&"a".to_string(), &1)));
+    }
+
+    #[test]
+    fn test_get_batch() {
+        let m = (0..50).map(|i| (i * 3, i)).collect::<FrozenFlatMap<_, _>>();
+        assert_eq!(m.get_batch(&[9, 1, 0]), vec![Some(&3), None, Some(&0)]);
+        assert_eq!(m.get_sorted_batch(&[0, 1, 9, 147, 150]), vec![Some(&0), None, Some(&3), Some(&49), None]);
+    }
+
+    #[test]
+    fn test_positional() {
+        let m = FrozenFlatMap::from([(10, 'a'), (20, 'b'), (30, 'c')]);
+        assert_eq!(m.get_index(1), Some((&20, &'b')));
+        assert_eq!(m.get_index(3), None);
+        assert_eq!(m.index_of(&30), Some(2));
+        assert_eq!(m.index_of(&25), None);
+        assert_eq!(m.rank(&25), 2);
+        assert_eq!(m.select(m.rank(&10)), Some((&10, &'a')));
+    }
+
+    #[test]
+    fn test_as_slice() {
+        let m = FrozenFlatMap::from([(3, 4), (1, 2)]);
+        assert_eq!(m.as_slice(), &[(1, 2), (3, 4)]);
+        assert_eq!(m.as_ref().chunks(1).count(), 2);
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let m = FrozenFlatMap::from([(1, 2), (3, 4), (5, 6)]);
+        assert_eq!(m.iter().rev().collect::<Vec<_>>(), vec![(&5, &6), (&3, &4), (&1, &2)]);
+        assert_eq!(m.keys().len(), 3);
+        assert_eq!((&m).into_iter().count(), 3);
+        let mut sum = 0;
+        for (k, v) in &m {
+            sum += k * v;
+        }
+        assert_eq!(sum, 44);
+        assert_eq!(m.into_iter().collect::<Vec<_>>(), vec![(1, 2), (3, 4), (5, 6)]);
+    }
+
+    #[test]
+    fn test_traits() {
+        use std::collections::HashSet;
+
+        let m = FrozenFlatMap::from([(3, 4), (1, 2)]);
+        assert_eq!(m.clone(), m);
+        assert_ne!(m, FrozenFlatMap::default());
+        assert_eq!(format!("{m:?}"), "{1: 2, 3: 4}");
+        assert!(FrozenFlatMap::<i32, i32>::default().is_empty());
+        assert_eq!(HashSet::from([m.clone(), m]).len(), 1);
+    }
+
+    #[test]
+    fn test_interpolation() {
+        use crate::Interpolation;
+
+        let m = (0..1000u64).map(|i| (1_700_000_000 + i * 60, i)).collect::<FrozenFlatMap<_, _, Interpolation>>();
+        assert_eq!(m.get(&(1_700_000_000 + 500 * 60)), Some(&500));
+        assert_eq!(m.get(&1_700_000_001), None);
+        assert_eq!(m.index_of(&1_700_000_000), Some(0));
+        assert_eq!(m.thaw().len(), 1000);
+    }
+
+    #[test]
+    fn test_map_values() {
+        let m = FrozenFlatMap::from([(1, 2), (3, 4)]).map_values(|k, v| (k + v).to_string());
+        assert_eq!(m.get(&3), Some(&"7".to_string()));
+        assert_eq!(m.len(), 2);
+    }
+
+    #[test]
+    fn test_range() {
+        let m = FrozenFlatMap::from([(1, 2), (3, 4), (5, 6), (7, 8)]);
+        assert_eq!(m.range(2..=5).collect::<Vec<_>>(), vec![(&3, &4), (&5, &6)]);
+
+        let m = FrozenFlatMap::from([("a".to_string(), 1), ("b".to_string(), 2), ("c".to_string(), 3)]);
+        let range = (Bound::Included("b"), Bound::Unbounded);
+        assert_eq!(m.range::<str>(range).map(|(_, v)| *v).collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_slice() {
+        let m = (0..10).map(|i| (i, i * 10)).collect::<FrozenFlatMap<_, _>>();
+        let view = m.slice(3..7);
+        assert_eq!(view.len(), 4);
+        assert_eq!(view.get(&3), Some(&30));
+        assert_eq!(view.get(&7), None);
+        assert_eq!(view.range(..5).map(|(k, _)| *k).collect::<Vec<_>>(), vec![3, 4]);
+        assert!(m.slice(20..).is_empty());
+    }
+
+    #[test]
+    fn test_merge() {
+        let a = FrozenFlatMap::from([(1, 10), (4, 40), (7, 70)]);
+        let b = FrozenFlatMap::from([(2, 2), (4, 4)]);
+        let c = FrozenFlatMap::from([(4, 400), (9, 900)]);
+        let latest = FrozenFlatMap::merge(&[&a, &b, &c], |_, _, v| *v);
+        assert_eq!(latest, FrozenFlatMap::from([(1, 10), (2, 2), (4, 400), (7, 70), (9, 900)]));
+        let sum = FrozenFlatMap::merge(&[&a, &b, &c], |_, acc, v| acc + v);
+        assert_eq!(sum.get(&4), Some(&444));
+        assert!(FrozenFlatMap::<i32, i32>::merge(&[], |_, v, _| v).is_empty());
+    }
+
+    #[test]
+    fn test_len() {
+        let m = FrozenFlatMap::from([(1, 2), (3, 4)]);
+        assert_eq!(m.len(), 2);
+        assert!(!m.is_empty());
+        assert_eq!(m.keys().collect::<Vec<_>>(), vec![&1, &3]);
+        assert_eq!(m.values().collect::<Vec<_>>(), vec![&2, &4]);
+    }
+}
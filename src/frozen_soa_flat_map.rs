@@ -0,0 +1,181 @@
+use crate::{sorted, Comparable, FlatMap, FrozenFlatMap, NaturalOrder};
+use std::{fmt, iter::Zip, ops::RangeBounds, slice};
+
+/// Immutable map that keeps keys and values in two separate boxed slices.
+///
+/// Lookups only touch the densely packed keys, which keeps binary search in cache when
+/// values are large. Otherwise behaves like [`FrozenFlatMap`].
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct FrozenSoaFlatMap<K: Ord, V> {
+    keys: Box<[K]>,
+    values: Box<[V]>,
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for FrozenSoaFlatMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> Default for FrozenSoaFlatMap<K, V> {
+    fn default() -> Self {
+        Self { keys: Box::new([]), values: Box::new([]) }
+    }
+}
+
+/// If there are duplicates, the last one is kept.
+impl<K: Ord, V> From<Vec<(K, V)>> for FrozenSoaFlatMap<K, V> {
+    fn from(items: Vec<(K, V)>) -> Self {
+        Self::from(FlatMap::from(items))
+    }
+}
+
+impl<K: Ord + Clone, V: Clone, const N: usize> From<[(K, V); N]> for FrozenSoaFlatMap<K, V> {
+    fn from(value: [(K, V); N]) -> Self {
+        Self::from(value.to_vec())
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for FrozenSoaFlatMap<K, V> {
+    fn from_iter<I: IntoIterator<Item=(K, V)>>(iter: I) -> Self {
+        Self::from(FlatMap::from_iter(iter))
+    }
+}
+
+impl<K: Ord, V> From<FlatMap<K, V>> for FrozenSoaFlatMap<K, V> {
+    fn from(map: FlatMap<K, V>) -> Self {
+        let (keys, values): (Vec<_>, Vec<_>) = map.into_iter().unzip();
+        Self { keys: keys.into_boxed_slice(), values: values.into_boxed_slice() }
+    }
+}
+
+impl<K: Ord, V> From<FrozenFlatMap<K, V>> for FrozenSoaFlatMap<K, V> {
+    fn from(map: FrozenFlatMap<K, V>) -> Self {
+        Self::from(map.thaw())
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a FrozenSoaFlatMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Zip<slice::Iter<'a, K>, slice::Iter<'a, V>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Ord, V> FrozenSoaFlatMap<K, V> {
+    /// Converts the map into a mutable [`FlatMap`] without re-sorting.
+    pub fn thaw(self) -> FlatMap<K, V> {
+        FlatMap::from_sorted_vec_unchecked(self.keys.into_vec().into_iter().zip(self.values.into_vec()).collect())
+    }
+
+    // lookup
+
+    pub fn contains_key<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> bool {
+        self.search(key).is_ok()
+    }
+
+    pub fn get<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<&V> {
+        self.search(key)
+            .ok()
+            .map(|i| &self.values[i])
+    }
+
+    pub fn get_key_value<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<(&K, &V)> {
+        self.search(key)
+            .ok()
+            .map(|i| (&self.keys[i], &self.values[i]))
+    }
+
+    // positional access
+
+    /// Returns the entry at position `index` in key order.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        Some((self.keys.get(index)?, &self.values[index]))
+    }
+
+    /// Returns the position of `key` in key order.
+    pub fn index_of<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<usize> {
+        self.search(key).ok()
+    }
+
+    // ranges
+
+    pub fn range<Q: ?Sized + Comparable<K>>(&self, range: impl RangeBounds<Q>) -> Zip<slice::Iter<'_, K>, slice::Iter<'_, V>> {
+        let range = sorted::key_range_indices(&self.keys, range, &NaturalOrder);
+        self.keys[range.clone()].iter().zip(self.values[range].iter())
+    }
+
+    // misc
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns all keys as a sorted slice.
+    pub fn keys_as_slice(&self) -> &[K] {
+        &self.keys
+    }
+
+    /// Returns all values as a slice, in key order.
+    pub fn values_as_slice(&self) -> &[V] {
+        &self.values
+    }
+
+    // iterators
+
+    pub fn iter(&self) -> Zip<slice::Iter<'_, K>, slice::Iter<'_, V>> {
+        self.keys.iter().zip(self.values.iter())
+    }
+
+    pub fn keys(&self) -> slice::Iter<'_, K> {
+        self.keys.iter()
+    }
+
+    pub fn values(&self) -> slice::Iter<'_, V> {
+        self.values.iter()
+    }
+
+    // internals
+
+    fn search<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Result<usize, usize> {
+        self.keys.binary_search_by(|probe| key.compare(probe).reverse())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from() {
+        let m = FrozenSoaFlatMap::from([(5, 'e'), (1, 'a'), (3, 'c'), (1, 'b')]);
+        assert_eq!(m.keys_as_slice(), &[1, 3, 5]);
+        assert_eq!(m.values_as_slice(), &['b', 'c', 'e']);
+        assert_eq!(m.thaw(), FlatMap::from([(1, 'b'), (3, 'c'), (5, 'e')]));
+    }
+
+    #[test]
+    fn test_get() {
+        let m = FrozenSoaFlatMap::from([("a".to_string(), 1), ("b".to_string(), 2)]);
+        assert!(m.contains_key("a"));
+        assert_eq!(m.get("b"), Some(&2));
+        assert_eq!(m.get("c"), None);
+        assert_eq!(m.get_key_value("a"), Some((&"a".to_string(), &1)));
+        assert_eq!(m.index_of("b"), Some(1));
+        assert_eq!(m.get_index(2), None);
+    }
+
+    #[test]
+    fn test_range() {
+        let m = (0..10).map(|i| (i, [i; 64])).collect::<FrozenSoaFlatMap<_, _>>();
+        assert_eq!(m.range(3..6).map(|(k, v)| k + v[63]).collect::<Vec<_>>(), vec![6, 8, 10]);
+        assert_eq!(m.range(..=1).rev().map(|(k, _)| *k).collect::<Vec<_>>(), vec![1, 0]);
+        assert_eq!(m.iter().len(), 10);
+    }
+}
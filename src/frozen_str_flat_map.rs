@@ -0,0 +1,240 @@
+use crate::sorted;
+use std::{fmt, iter::FusedIterator, ops::{Range, RangeBounds}, slice};
+
+/// Immutable map with string keys stored back to back in a single `Box<str>`.
+///
+/// Compared to a [`FrozenFlatMap<String, V>`](crate::FrozenFlatMap) this saves one heap
+/// allocation and three words per key, and binary search reads the keys from one contiguous
+/// buffer instead of following a pointer for every probe.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct FrozenStrFlatMap<V> {
+    keys: Box<str>,
+    /// Start of every key in `keys`, followed by the total length.
+    offsets: Box<[usize]>,
+    values: Box<[V]>,
+}
+
+impl<V: fmt::Debug> fmt::Debug for FrozenStrFlatMap<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<V> Default for FrozenStrFlatMap<V> {
+    fn default() -> Self {
+        Self { keys: Box::default(), offsets: Box::new([0]), values: Box::new([]) }
+    }
+}
+
+/// If there are duplicates, the last one is kept.
+impl<V, T: AsRef<str>> From<Vec<(T, V)>> for FrozenStrFlatMap<V> {
+    fn from(mut items: Vec<(T, V)>) -> Self {
+        sorted::sort_dedup(&mut items, &|a: &T, b: &T| a.as_ref().cmp(b.as_ref()));
+
+        let mut keys = String::with_capacity(items.iter().map(|(k, _)| k.as_ref().len()).sum());
+        let mut offsets = Vec::with_capacity(items.len() + 1);
+        let mut values = Vec::with_capacity(items.len());
+        for (key, value) in items {
+            offsets.push(keys.len());
+            keys.push_str(key.as_ref());
+            values.push(value);
+        }
+        offsets.push(keys.len());
+
+        Self {
+            keys: keys.into_boxed_str(),
+            offsets: offsets.into_boxed_slice(),
+            values: values.into_boxed_slice(),
+        }
+    }
+}
+
+impl<V, T: AsRef<str>, const N: usize> From<[(T, V); N]> for FrozenStrFlatMap<V> {
+    fn from(value: [(T, V); N]) -> Self {
+        Self::from(Vec::from(value))
+    }
+}
+
+impl<V, T: AsRef<str>> FromIterator<(T, V)> for FrozenStrFlatMap<V> {
+    fn from_iter<I: IntoIterator<Item=(T, V)>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<'a, V> IntoIterator for &'a FrozenStrFlatMap<V> {
+    type Item = (&'a str, &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<V> FrozenStrFlatMap<V> {
+    // lookup
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.search(key).is_ok()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.search(key)
+            .ok()
+            .map(|i| &self.values[i])
+    }
+
+    pub fn get_key_value(&self, key: &str) -> Option<(&str, &V)> {
+        self.search(key)
+            .ok()
+            .map(|i| (self.key(i), &self.values[i]))
+    }
+
+    // positional access
+
+    /// Returns the entry at position `index` in key order.
+    pub fn get_index(&self, index: usize) -> Option<(&str, &V)> {
+        let value = self.values.get(index)?;
+        Some((self.key(index), value))
+    }
+
+    /// Returns the position of `key` in key order.
+    pub fn index_of(&self, key: &str) -> Option<usize> {
+        self.search(key).ok()
+    }
+
+    /// Returns the number of keys strictly less than `key`.
+    pub fn rank(&self, key: &str) -> usize {
+        self.search(key).unwrap_or_else(|i| i)
+    }
+
+    // ranges
+
+    /// # Panics
+    ///
+    /// Panics if the start of `range` is greater than its end, like `FlatMap::range`.
+    pub fn range<'q>(&self, range: impl RangeBounds<&'q str>) -> Iter<'_, V> {
+        let indices = sorted::bound_indices(
+            range,
+            self.len(),
+            |key: &&str| sorted::index_partition_point(self.len(), |i| self.key(i) < *key),
+            |key: &&str| sorted::index_partition_point(self.len(), |i| self.key(i) <= *key),
+        );
+        assert!(indices.start <= indices.end, "range start is greater than range end in FrozenStrFlatMap");
+        Iter { map: self, indices }
+    }
+
+    // misc
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns all keys concatenated in key order.
+    pub fn keys_as_str(&self) -> &str {
+        &self.keys
+    }
+
+    /// Returns all values as a slice, in key order.
+    pub fn values_as_slice(&self) -> &[V] {
+        &self.values
+    }
+
+    // iterators
+
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter { map: self, indices: 0..self.len() }
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item=&str> + ExactSizeIterator {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> slice::Iter<'_, V> {
+        self.values.iter()
+    }
+
+    // internals
+
+    fn key(&self, index: usize) -> &str {
+        &self.keys[self.offsets[index]..self.offsets[index + 1]]
+    }
+
+    fn search(&self, key: &str) -> Result<usize, usize> {
+        let index = sorted::index_partition_point(self.len(), |i| self.key(i) < key);
+        if index < self.len() && self.key(index) == key {
+            Ok(index)
+        } else {
+            Err(index)
+        }
+    }
+}
+
+/// An iterator over the entries of a [`FrozenStrFlatMap`] in key order.
+#[derive(Clone)]
+pub struct Iter<'a, V> {
+    map: &'a FrozenStrFlatMap<V>,
+    indices: Range<usize>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (&'a str, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.indices.next().map(|i| (self.map.key(i), &self.map.values[i]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+impl<V> DoubleEndedIterator for Iter<'_, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.indices.next_back().map(|i| (self.map.key(i), &self.map.values[i]))
+    }
+}
+
+impl<V> ExactSizeIterator for Iter<'_, V> {}
+
+impl<V> FusedIterator for Iter<'_, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ops::Bound;
+
+    #[test]
+    fn test_get() {
+        let m = FrozenStrFlatMap::from([("pear", 3), ("apple", 1), ("fig", 2), ("apple", 4)]);
+        assert_eq!(m.len(), 3);
+        assert_eq!(m.keys_as_str(), "applefigpear");
+        assert_eq!(m.get("apple"), Some(&4));
+        assert_eq!(m.get("app"), None);
+        assert_eq!(m.get_key_value("fig"), Some(("fig", &2)));
+        assert_eq!(m.get_index(2), Some(("pear", &3)));
+        assert_eq!(m.get_index(3), None);
+        assert_eq!(m.rank("b"), 1);
+        assert!(FrozenStrFlatMap::<()>::default().get("").is_none());
+    }
+
+    #[test]
+    fn test_iter() {
+        let m = (0..20).map(|i| (i.to_string(), i)).collect::<FrozenStrFlatMap<_>>();
+        assert_eq!(m.keys().take(3).collect::<Vec<_>>(), vec!["0", "1", "10"]);
+        assert_eq!(m.range("3".."5").map(|(_, v)| *v).collect::<Vec<_>>(), vec![3, 4]);
+        assert_eq!(m.range((Bound::Excluded("1"), Bound::Excluded("11"))).count(), 1);
+        assert_eq!(m.iter().next_back(), Some(("9", &9)));
+        assert_eq!((&m).into_iter().len(), 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    fn test_range_reversed() {
+        let m = FrozenStrFlatMap::from([("1", 1), ("9", 9)]);
+        let _ = m.range((Bound::Included("9"), Bound::Excluded("1")));
+    }
+}
@@ -0,0 +1,142 @@
+use crate::FrozenFlatMap;
+use std::{borrow::Borrow, collections::hash_map::RandomState, fmt, hash::{BuildHasher, Hash}, ops::Deref};
+
+const EMPTY: usize = usize::MAX;
+
+/// A [`FrozenFlatMap`] with a secondary hash index, built by [`FrozenFlatMap::with_hash_index`].
+///
+/// Exact-key lookups go through the hash index in O(1) expected time, everything else
+/// (ranges, iteration, positional access) through the sorted entries, which it derefs to.
+/// The index is an open-addressing table of entry positions, so keys are not duplicated.
+pub struct IndexedFrozenFlatMap<K: Ord, V, S = RandomState> {
+    map: FrozenFlatMap<K, V>,
+    slots: Box<[usize]>,
+    hasher: S,
+}
+
+impl<K: Ord + Clone, V: Clone, S: Clone> Clone for IndexedFrozenFlatMap<K, V, S> {
+    fn clone(&self) -> Self {
+        Self { map: self.map.clone(), slots: self.slots.clone(), hasher: self.hasher.clone() }
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug, S> fmt::Debug for IndexedFrozenFlatMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<K: Ord, V, S> Deref for IndexedFrozenFlatMap<K, V, S> {
+    type Target = FrozenFlatMap<K, V>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl<K: Ord + Hash, V> FrozenFlatMap<K, V> {
+    /// Builds a hash index over the keys in O(n), for O(1) `get` and `contains_key`.
+    pub fn with_hash_index(self) -> IndexedFrozenFlatMap<K, V> {
+        self.with_hash_index_and_hasher(RandomState::new())
+    }
+
+    pub fn with_hash_index_and_hasher<S: BuildHasher>(self, hasher: S) -> IndexedFrozenFlatMap<K, V, S> {
+        let mask = (self.len() * 2).next_power_of_two() - 1;
+        let mut slots = vec![EMPTY; mask + 1].into_boxed_slice();
+        for (position, key) in self.keys().enumerate() {
+            let mut i = hasher.hash_one(key) as usize & mask;
+            while slots[i] != EMPTY {
+                i = (i + 1) & mask;
+            }
+            slots[i] = position;
+        }
+        IndexedFrozenFlatMap { map: self, slots, hasher }
+    }
+}
+
+impl<K: Ord + Hash, V, S: BuildHasher> IndexedFrozenFlatMap<K, V, S> {
+    /// Drops the hash index.
+    pub fn into_frozen(self) -> FrozenFlatMap<K, V> {
+        self.map
+    }
+
+    // lookup
+
+    pub fn contains_key<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.position(key).is_some()
+    }
+
+    pub fn get<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    pub fn get_key_value<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+    {
+        self.position(key).and_then(|i| self.map.get_index(i))
+    }
+
+    /// Returns the position of `key` in key order.
+    pub fn index_of<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+    {
+        self.position(key)
+    }
+
+    // internals
+
+    fn position<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+    {
+        let items = self.map.as_slice();
+        let mask = self.slots.len() - 1;
+        let mut i = self.hasher.hash_one(key) as usize & mask;
+        loop {
+            match self.slots[i] {
+                EMPTY => return None,
+                position if items[position].0.borrow() == key => return Some(position),
+                _ => i = (i + 1) & mask,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get() {
+        let m = (0..100).map(|i| (i.to_string(), i)).collect::<FrozenFlatMap<_, _>>().with_hash_index();
+        for i in 0..100 {
+            assert_eq!(m.get(i.to_string().as_str()), Some(&i));
+        }
+        assert!(!m.contains_key("100"));
+        assert_eq!(m.index_of("10"), Some(2));
+        assert_eq!(m.get_key_value("7"), Some((&"7".to_string(), &7)));
+    }
+
+    #[test]
+    fn test_ordered_access() {
+        let m = FrozenFlatMap::from([(3, 'c'), (1, 'a'), (2, 'b')]).with_hash_index();
+        assert_eq!(m.range(2..).collect::<Vec<_>>(), vec![(&2, &'b'), (&3, &'c')]);
+        assert_eq!(m.keys().collect::<Vec<_>>(), vec![&1, &2, &3]);
+        assert_eq!(m.into_frozen().len(), 3);
+    }
+
+    #[test]
+    fn test_empty() {
+        let m = FrozenFlatMap::<i32, i32>::default().with_hash_index();
+        assert!(!m.contains_key(&0));
+        assert!(m.is_empty());
+    }
+}
@@ -1,5 +1,61 @@
-mod flat_map;
-mod flat_set;
+mod bits;
+mod bloom_filter;
+pub mod codec;
+pub mod codegen;
+mod compare;
+pub mod elias_fano_set;
+mod error;
+mod filtered_frozen_flat_map;
+mod flat_interval_map;
+pub mod flat_map;
+mod flat_map_view;
+mod flat_multi_set;
+mod flat_range_set;
+pub mod flat_set;
+mod front_coded_flat_map;
+mod frozen_flat_map;
+mod frozen_soa_flat_map;
+pub mod frozen_str_flat_map;
+mod indexed_frozen_flat_map;
+#[cfg(feature = "mmap")]
+mod mmap_flat_map;
+pub mod overlay_flat_map;
+pub mod packed_flat_map;
+#[cfg(feature = "bytemuck")]
+mod pod;
+#[cfg(feature = "bytemuck")]
+mod pod_flat_map_view;
+mod search;
+#[cfg(feature = "serde")]
+mod serde_impls;
+mod shared_flat_map;
+mod sorted;
+mod storage;
 
-pub use flat_map::FlatMap;
-pub use flat_set::FlatSet;
+pub use bloom_filter::BloomFilter;
+pub use compare::{Comparable, Compare, NaturalOrder, Reversed};
+pub use elias_fano_set::EliasFanoSet;
+pub use error::{DuplicateKeyError, NotSortedError, OutOfOrderError, OverlapError, UnorderedKeyError};
+pub use filtered_frozen_flat_map::FilteredFrozenFlatMap;
+pub use flat_interval_map::FlatIntervalMap;
+pub use flat_map::{FlatMap, FlatMapBuilder};
+#[cfg(feature = "macros")]
+pub use flat_collections_macros::frozen_flat_map;
+pub use flat_map_view::FlatMapView;
+pub use flat_multi_set::FlatMultiSet;
+pub use flat_range_set::FlatRangeSet;
+pub use flat_set::{FlatSet, SetExpr};
+pub use front_coded_flat_map::{ByteKey, FrontCodedFlatMap};
+pub use frozen_flat_map::FrozenFlatMap;
+pub use frozen_soa_flat_map::FrozenSoaFlatMap;
+pub use frozen_str_flat_map::FrozenStrFlatMap;
+pub use indexed_frozen_flat_map::IndexedFrozenFlatMap;
+#[cfg(feature = "mmap")]
+pub use mmap_flat_map::MmapFlatMap;
+pub use overlay_flat_map::OverlayFlatMap;
+pub use packed_flat_map::{PackedFlatMap, PackedKey};
+#[cfg(feature = "bytemuck")]
+pub use pod_flat_map_view::PodFlatMapView;
+pub use search::{Branchless, Interpolate, Interpolation};
+pub use shared_flat_map::SharedFlatMap;
+pub use storage::{Storage, StorageMut};
@@ -0,0 +1,196 @@
+use crate::{pod, Comparable, FrozenFlatMap, NotSortedError, PodFlatMapView};
+use bytemuck::Pod;
+use memmap2::Mmap;
+use std::{fmt, fs::File, io, iter::Zip, marker::PhantomData, ops::{Range, RangeBounds}, slice};
+
+/// Immutable map over a memory-mapped file of plain-old-data keys and values.
+///
+/// The file is written by [`FrozenFlatMap::write_pod_to`] and paged in lazily by the OS, so
+/// opening it is O(1) regardless of size and the pages are shared between processes that map
+/// the same file. Lookups go through [`PodFlatMapView`].
+pub struct MmapFlatMap<K: Ord + Pod, V: Pod> {
+    mmap: Mmap,
+    keys: Range<usize>,
+    values: Range<usize>,
+    marker: PhantomData<(K, V)>,
+}
+
+impl<K: Ord + Pod + fmt::Debug, V: Pod + fmt::Debug> fmt::Debug for MmapFlatMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K: Ord + Pod, V: Pod> IntoIterator for &'a MmapFlatMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Zip<slice::Iter<'a, K>, slice::Iter<'a, V>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Ord + Pod, V: Pod> MmapFlatMap<K, V> {
+    /// Maps `file` into memory, validating its header but not the order of its keys,
+    /// see [`MmapFlatMap::check_invariants`].
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while it is mapped, see [`Mmap::map`].
+    pub unsafe fn map(file: &File) -> io::Result<Self> {
+        let mmap = Mmap::map(file)?;
+        let (keys, values) = pod::parse::<K, V>(&mmap)?;
+        Ok(Self { mmap, keys, values, marker: PhantomData })
+    }
+
+    /// Returns a view of the mapped entries.
+    pub fn as_view(&self) -> PodFlatMapView<'_, K, V> {
+        PodFlatMapView::from_parts(&self.mmap, self.keys.clone(), self.values.clone())
+    }
+
+    /// Returns the mapped bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.mmap
+    }
+
+    /// Copies the entries into a [`FrozenFlatMap`] on the heap.
+    pub fn to_frozen(&self) -> FrozenFlatMap<K, V> {
+        self.as_view().to_frozen()
+    }
+
+    // lookup
+
+    pub fn contains_key<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> bool {
+        self.as_view().contains_key(key)
+    }
+
+    pub fn get<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<&V> {
+        self.as_view().get(key)
+    }
+
+    pub fn get_key_value<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<(&K, &V)> {
+        self.as_view().get_key_value(key)
+    }
+
+    // positional access
+
+    /// Returns the entry at position `index` in key order.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.as_view().get_index(index)
+    }
+
+    /// Returns the position of `key` in key order.
+    pub fn index_of<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<usize> {
+        self.as_view().index_of(key)
+    }
+
+    /// Returns the number of keys strictly less than `key`.
+    pub fn rank<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> usize {
+        self.as_view().rank(key)
+    }
+
+    // ranges
+
+    pub fn range<Q: ?Sized + Comparable<K>>(&self, range: impl RangeBounds<Q>) -> Zip<slice::Iter<'_, K>, slice::Iter<'_, V>> {
+        self.as_view().range(range)
+    }
+
+    // misc
+
+    pub fn is_empty(&self) -> bool {
+        self.as_view().is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.as_view().len()
+    }
+
+    /// Verifies that the keys are strictly increasing. This reads every key, so it
+    /// pages in the whole key array.
+    pub fn check_invariants(&self) -> Result<(), NotSortedError> {
+        self.as_view().check_invariants()
+    }
+
+    /// Returns all keys as a sorted slice.
+    pub fn keys_as_slice(&self) -> &[K] {
+        self.as_view().keys_as_slice()
+    }
+
+    /// Returns all values as a slice, in key order.
+    pub fn values_as_slice(&self) -> &[V] {
+        self.as_view().values_as_slice()
+    }
+
+    // iterators
+
+    pub fn iter(&self) -> Zip<slice::Iter<'_, K>, slice::Iter<'_, V>> {
+        self.as_view().iter()
+    }
+
+    pub fn keys(&self) -> slice::Iter<'_, K> {
+        self.as_view().keys()
+    }
+
+    pub fn values(&self) -> slice::Iter<'_, V> {
+        self.as_view().values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::PathBuf};
+
+    fn temp_file(name: &str, bytes: &[u8]) -> (PathBuf, File) {
+        let path = std::env::temp_dir().join(format!("flat_collections_{}_{name}", std::process::id()));
+        fs::write(&path, bytes).unwrap();
+        let file = File::open(&path).unwrap();
+        (path, file)
+    }
+
+    #[test]
+    fn test_map() {
+        let m = (0..1000u64).map(|i| (i * 3, i as u8)).collect::<FrozenFlatMap<_, _>>();
+        let mut bytes = Vec::new();
+        m.write_pod_to(&mut bytes).unwrap();
+        let (path, file) = temp_file("map", &bytes);
+
+        let mapped = unsafe { MmapFlatMap::<u64, u8>::map(&file) }.unwrap();
+        assert_eq!(mapped.len(), 1000);
+        assert_eq!(mapped.get(&300), Some(&100));
+        assert_eq!(mapped.get(&301), None);
+        assert_eq!(mapped.get_index(2), Some((&6, &2)));
+        assert_eq!(mapped.rank(&4), 2);
+        assert_eq!(mapped.range(3..10).map(|(k, _)| *k).collect::<Vec<_>>(), vec![3, 6, 9]);
+        assert_eq!(mapped.check_invariants(), Ok(()));
+        assert_eq!(mapped.to_frozen(), m);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        let mut bytes = Vec::new();
+        FrozenFlatMap::from([(2u32, 1u16), (1, 0)]).write_pod_to(&mut bytes).unwrap();
+
+        let map = |name, bytes: &[u8]| {
+            let (path, file) = temp_file(name, bytes);
+            let result = unsafe { MmapFlatMap::<u32, u16>::map(&file) }.map(|m| m.len());
+            fs::remove_file(path).unwrap();
+            result.map_err(|e| e.kind())
+        };
+        assert_eq!(map("ok", &bytes), Ok(2));
+        assert_eq!(map("short", &bytes[..bytes.len() - 1]), Err(io::ErrorKind::UnexpectedEof));
+        assert_eq!(map("magic", &[b"JUNK", &bytes[4..]].concat()), Err(io::ErrorKind::InvalidData));
+
+        let (path, file) = temp_file("size", &bytes);
+        assert!(unsafe { MmapFlatMap::<u64, u16>::map(&file) }.is_err());
+        fs::remove_file(path).unwrap();
+
+        let mut unsorted = bytes.clone();
+        unsorted[32..36].copy_from_slice(&5u32.to_ne_bytes());
+        let (path, file) = temp_file("unsorted", &unsorted);
+        let mapped = unsafe { MmapFlatMap::<u32, u16>::map(&file) }.unwrap();
+        assert_eq!(mapped.check_invariants().unwrap_err().index(), 1);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{flat_map, Comparable, FlatMap, FrozenFlatMap};
+use std::{cmp::Ordering, fmt, iter::{FusedIterator, Peekable}, mem};
+
+/// Map made of an immutable [`FrozenFlatMap`] base and a small mutable delta on top of it.
+///
+/// Inserts and removals only touch the delta, where removed base keys are recorded as
+/// tombstones, so editing a huge read-mostly table doesn't copy it. Lookups consult the delta
+/// first and iteration merges both in key order. [`OverlayFlatMap::compact`] folds the delta
+/// into a new base once it grows too large.
+pub struct OverlayFlatMap<K: Ord, V> {
+    base: FrozenFlatMap<K, V>,
+    delta: FlatMap<K, Option<V>>,
+    len: usize,
+}
+
+impl<K: Ord + Clone, V: Clone> Clone for OverlayFlatMap<K, V> {
+    fn clone(&self) -> Self {
+        Self { base: self.base.clone(), delta: self.delta.clone(), len: self.len }
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for OverlayFlatMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> Default for OverlayFlatMap<K, V> {
+    fn default() -> Self {
+        Self::new(FrozenFlatMap::default())
+    }
+}
+
+impl<K: Ord, V> From<FrozenFlatMap<K, V>> for OverlayFlatMap<K, V> {
+    fn from(base: FrozenFlatMap<K, V>) -> Self {
+        Self::new(base)
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a OverlayFlatMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Ord, V> OverlayFlatMap<K, V> {
+    /// Creates an overlay with an empty delta on top of `base`.
+    pub fn new(base: FrozenFlatMap<K, V>) -> Self {
+        let len = base.len();
+        Self { base, delta: FlatMap::new(), len }
+    }
+
+    /// Returns the immutable base, without the changes in the delta.
+    pub fn base(&self) -> &FrozenFlatMap<K, V> {
+        &self.base
+    }
+
+    /// Returns the number of entries in the delta, tombstones included.
+    pub fn delta_len(&self) -> usize {
+        self.delta.len()
+    }
+
+    /// Folds the delta into a new base in O(n + m).
+    pub fn compact(&mut self) {
+        let overlay = mem::take(self);
+        *self = Self::new(overlay.into_frozen());
+    }
+
+    /// Merges the delta into the base and returns the result, in O(n + m).
+    pub fn into_frozen(self) -> FrozenFlatMap<K, V> {
+        if self.delta.is_empty() {
+            return self.base;
+        }
+
+        let mut items = Vec::with_capacity(self.len);
+        let mut base = self.base.into_iter().peekable();
+        let mut delta = self.delta.into_iter().peekable();
+        while let (Some((b, _)), Some((d, _))) = (base.peek(), delta.peek()) {
+            match b.cmp(d) {
+                Ordering::Less => items.extend(base.next()),
+                Ordering::Greater => items.extend(delta.next().and_then(|(k, v)| Some((k, v?)))),
+                Ordering::Equal => {
+                    base.next();
+                    items.extend(delta.next().and_then(|(k, v)| Some((k, v?))));
+                }
+            }
+        }
+        items.extend(base);
+        items.extend(delta.filter_map(|(k, v)| Some((k, v?))));
+        FrozenFlatMap::from_sorted_unchecked(items.into_boxed_slice())
+    }
+
+    // lookup
+
+    pub fn contains_key<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn get<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<&V> {
+        match self.delta.get(key) {
+            Some(value) => value.as_ref(),
+            None => self.base.get(key),
+        }
+    }
+
+    pub fn get_key_value<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<(&K, &V)> {
+        match self.delta.get_key_value(key) {
+            Some((k, value)) => Some((k, value.as_ref()?)),
+            None => self.base.get_key_value(key),
+        }
+    }
+
+    // modification
+
+    /// Inserts an entry into the delta, shadowing the base.
+    pub fn insert(&mut self, key: K, value: V) {
+        if !self.contains_key(&key) {
+            self.len += 1;
+        }
+        self.delta.insert(key, Some(value));
+    }
+
+    /// Removes `key`, recording a tombstone if it is present in the base. Returns whether
+    /// the key was present.
+    pub fn remove<Q: ?Sized + Comparable<K>>(&mut self, key: &Q) -> bool
+    where
+        K: Clone,
+    {
+        let in_base = self.base.get_key_value(key).map(|(k, _)| k);
+        match (self.delta.get_mut(key), in_base) {
+            (Some(None), _) => return false,
+            (Some(value), Some(_)) => *value = None,
+            (Some(_), None) => {
+                self.delta.remove(key);
+            }
+            (None, Some(k)) => {
+                self.delta.insert(k.clone(), None);
+            }
+            (None, None) => return false,
+        }
+        self.len -= 1;
+        true
+    }
+
+    // misc
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    // iterators
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter { base: self.base.iter().peekable(), delta: self.delta.iter().peekable(), len: self.len }
+    }
+}
+
+/// An iterator over the entries of an [`OverlayFlatMap`] in key order.
+pub struct Iter<'a, K, V> {
+    base: Peekable<flat_map::Iter<'a, K, V>>,
+    delta: Peekable<flat_map::Iter<'a, K, Option<V>>>,
+    len: usize,
+}
+
+impl<'a, K: Ord, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let ordering = match (self.base.peek(), self.delta.peek()) {
+                (Some((b, _)), Some((d, _))) => b.cmp(d),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => return None,
+            };
+            if ordering == Ordering::Less {
+                self.len -= 1;
+                return self.base.next();
+            }
+            if ordering == Ordering::Equal {
+                self.base.next();
+            }
+            if let Some((k, Some(v))) = self.delta.next() {
+                self.len -= 1;
+                return Some((k, v));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K: Ord, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K: Ord, V> FusedIterator for Iter<'_, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlay() {
+        let mut m = OverlayFlatMap::new(FrozenFlatMap::from([(1, 'a'), (3, 'c'), (5, 'e')]));
+        m.insert(2, 'b');
+        m.insert(3, 'C');
+        assert!(m.remove(&5));
+        assert!(!m.remove(&5));
+        assert!(!m.remove(&4));
+        m.insert(6, 'f');
+        assert!(m.remove(&6));
+
+        assert_eq!(m.get(&3), Some(&'C'));
+        assert_eq!(m.get(&5), None);
+        assert_eq!(m.base().get(&5), Some(&'e'));
+        assert_eq!(m.len(), 3);
+        assert_eq!(m.delta_len(), 3);
+        assert_eq!(m.iter().len(), 3);
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(&1, &'a'), (&2, &'b'), (&3, &'C')]);
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut m = OverlayFlatMap::new(FrozenFlatMap::from([(1, 1), (2, 2)]));
+        m.remove(&1);
+        m.insert(0, 0);
+        m.insert(3, 3);
+        m.compact();
+        assert_eq!(m.delta_len(), 0);
+        assert_eq!(m.base(), &FrozenFlatMap::from([(0, 0), (2, 2), (3, 3)]));
+        m.insert(1, 1);
+        assert_eq!(m.into_frozen().len(), 4);
+    }
+}
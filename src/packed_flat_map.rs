@@ -0,0 +1,322 @@
+use crate::{bits, sorted, FlatMap};
+use std::{fmt, iter::FusedIterator, marker::PhantomData, ops::{Range, RangeBounds}, slice};
+
+/// Number of keys per block. Each block stores its smallest key in full and the other keys as
+/// offsets from it, all with the same number of bits.
+const BLOCK_LEN: usize = 128;
+
+/// Integer key types that can be stored in a [`PackedFlatMap`].
+pub trait PackedKey: Ord + Copy {
+    /// Maps the key to a `u64` with the same ordering.
+    fn to_u64(self) -> u64;
+
+    /// Inverse of [`PackedKey::to_u64`].
+    fn from_u64(n: u64) -> Self;
+}
+
+macro_rules! impl_packed_key {
+    ($($t:ty),*) => {$(
+        impl PackedKey for $t {
+            fn to_u64(self) -> u64 {
+                self as u64
+            }
+
+            fn from_u64(n: u64) -> Self {
+                n as $t
+            }
+        }
+    )*};
+    (signed: $($t:ty => $u:ty),*) => {$(
+        impl PackedKey for $t {
+            fn to_u64(self) -> u64 {
+                (self as $u ^ (1 << (<$u>::BITS - 1))) as u64
+            }
+
+            fn from_u64(n: u64) -> Self {
+                (n as $u ^ (1 << (<$u>::BITS - 1))) as $t
+            }
+        }
+    )*};
+}
+
+impl_packed_key!(u8, u16, u32, u64, usize);
+impl_packed_key!(signed: i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize);
+
+/// Immutable map with integer keys compressed by frame-of-reference bit packing.
+///
+/// Keys are split into blocks of 128. Lookups binary search the smallest key of every block,
+/// then binary search the packed offsets inside a single block, so they stay O(log n). Dense
+/// key spaces such as IDs need only a few bits per key instead of the full key width.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct PackedFlatMap<K: PackedKey, V> {
+    /// Smallest key of every block, as returned by [`PackedKey::to_u64`].
+    mins: Box<[u64]>,
+    /// Bits per offset in every block.
+    widths: Box<[u8]>,
+    /// First word of every block in `words`.
+    starts: Box<[usize]>,
+    words: Box<[u64]>,
+    values: Box<[V]>,
+    marker: PhantomData<K>,
+}
+
+impl<K: PackedKey + fmt::Debug, V: fmt::Debug> fmt::Debug for PackedFlatMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PackedKey, V> Default for PackedFlatMap<K, V> {
+    fn default() -> Self {
+        Self::from(FlatMap::new())
+    }
+}
+
+/// If there are duplicates, the last one is kept.
+impl<K: PackedKey, V> From<Vec<(K, V)>> for PackedFlatMap<K, V> {
+    fn from(items: Vec<(K, V)>) -> Self {
+        Self::from(FlatMap::from(items))
+    }
+}
+
+impl<K: PackedKey, V, const N: usize> From<[(K, V); N]> for PackedFlatMap<K, V> {
+    fn from(value: [(K, V); N]) -> Self {
+        Self::from(Vec::from(value))
+    }
+}
+
+impl<K: PackedKey, V> FromIterator<(K, V)> for PackedFlatMap<K, V> {
+    fn from_iter<I: IntoIterator<Item=(K, V)>>(iter: I) -> Self {
+        Self::from(FlatMap::from_iter(iter))
+    }
+}
+
+impl<K: PackedKey, V> From<FlatMap<K, V>> for PackedFlatMap<K, V> {
+    fn from(map: FlatMap<K, V>) -> Self {
+        let (keys, values): (Vec<_>, Vec<_>) = map.into_iter().map(|(k, v)| (k.to_u64(), v)).unzip();
+
+        let mut mins = Vec::with_capacity(keys.len().div_ceil(BLOCK_LEN));
+        let mut widths = Vec::with_capacity(mins.capacity());
+        let mut starts = Vec::with_capacity(mins.capacity());
+        let mut words = Vec::new();
+        for block in keys.chunks(BLOCK_LEN) {
+            let min = block[0];
+            let width = (u64::BITS - (block[block.len() - 1] - min).leading_zeros()) as usize;
+            mins.push(min);
+            widths.push(width as u8);
+            starts.push(words.len());
+
+            let start = words.len();
+            words.resize(start + bits::words_for(block.len(), width), 0);
+            for (i, key) in block.iter().enumerate() {
+                bits::pack(&mut words[start..], i, width, key - min);
+            }
+        }
+
+        Self {
+            mins: mins.into_boxed_slice(),
+            widths: widths.into_boxed_slice(),
+            starts: starts.into_boxed_slice(),
+            words: words.into_boxed_slice(),
+            values: values.into_boxed_slice(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, K: PackedKey, V> IntoIterator for &'a PackedFlatMap<K, V> {
+    type Item = (K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: PackedKey, V> PackedFlatMap<K, V> {
+    /// Converts the map into a mutable [`FlatMap`] without re-sorting.
+    pub fn thaw(self) -> FlatMap<K, V> {
+        let keys = (0..self.len()).map(|i| self.key(i)).collect::<Vec<_>>();
+        FlatMap::from_sorted_vec_unchecked(keys.into_iter().zip(self.values.into_vec()).collect())
+    }
+
+    // lookup
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.index_of(key).is_some()
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.index_of(key).map(|i| &self.values[i])
+    }
+
+    pub fn get_key_value(&self, key: &K) -> Option<(K, &V)> {
+        self.index_of(key).map(|i| (*key, &self.values[i]))
+    }
+
+    // positional access
+
+    /// Returns the entry at position `index` in key order.
+    pub fn get_index(&self, index: usize) -> Option<(K, &V)> {
+        let value = self.values.get(index)?;
+        Some((self.key(index), value))
+    }
+
+    /// Returns the position of `key` in key order.
+    pub fn index_of(&self, key: &K) -> Option<usize> {
+        let index = self.rank(key);
+        (index < self.len() && self.key(index) == *key).then_some(index)
+    }
+
+    /// Returns the number of keys strictly less than `key`.
+    pub fn rank(&self, key: &K) -> usize {
+        self.lower_bound(key.to_u64())
+    }
+
+    // ranges
+
+    /// # Panics
+    ///
+    /// Panics if the start of `range` is greater than its end, like `FlatMap::range`.
+    pub fn range(&self, range: impl RangeBounds<K>) -> Iter<'_, K, V> {
+        let indices = sorted::bound_indices(
+            range,
+            self.len(),
+            |key: &K| self.lower_bound(key.to_u64()),
+            |key: &K| self.upper_bound(key.to_u64()),
+        );
+        assert!(indices.start <= indices.end, "range start is greater than range end in PackedFlatMap");
+        Iter { map: self, indices }
+    }
+
+    // misc
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns the number of bytes used to store the encoded keys.
+    pub fn key_bytes(&self) -> usize {
+        self.words.len() * 8 + self.mins.len() * (8 + 1 + size_of::<usize>())
+    }
+
+    /// Returns all values as a slice, in key order.
+    pub fn values_as_slice(&self) -> &[V] {
+        &self.values
+    }
+
+    // iterators
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter { map: self, indices: 0..self.len() }
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item=K> + ExactSizeIterator + '_ {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> slice::Iter<'_, V> {
+        self.values.iter()
+    }
+
+    // internals
+
+    fn key(&self, index: usize) -> K {
+        let block = index / BLOCK_LEN;
+        K::from_u64(self.mins[block] + self.offset(block, index % BLOCK_LEN))
+    }
+
+    /// Unpacks the offset of the `i`-th key of `block` from its minimum.
+    fn offset(&self, block: usize, i: usize) -> u64 {
+        bits::unpack(&self.words[self.starts[block]..], i, self.widths[block] as usize)
+    }
+
+    /// Position of the first key that is greater than or equal to `key`.
+    fn lower_bound(&self, key: u64) -> usize {
+        let Some(block) = self.mins.partition_point(|&min| min <= key).checked_sub(1) else {
+            return 0;
+        };
+        let len = BLOCK_LEN.min(self.len() - block * BLOCK_LEN);
+        let target = key - self.mins[block];
+
+        block * BLOCK_LEN + sorted::index_partition_point(len, |i| self.offset(block, i) < target)
+    }
+
+    /// Position of the first key that is greater than `key`.
+    fn upper_bound(&self, key: u64) -> usize {
+        key.checked_add(1).map_or(self.len(), |key| self.lower_bound(key))
+    }
+}
+
+/// An iterator over the entries of a [`PackedFlatMap`] in key order.
+#[derive(Clone)]
+pub struct Iter<'a, K: PackedKey, V> {
+    map: &'a PackedFlatMap<K, V>,
+    indices: Range<usize>,
+}
+
+impl<'a, K: PackedKey, V> Iterator for Iter<'a, K, V> {
+    type Item = (K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.indices.next().map(|i| (self.map.key(i), &self.map.values[i]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+impl<K: PackedKey, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.indices.next_back().map(|i| (self.map.key(i), &self.map.values[i]))
+    }
+}
+
+impl<K: PackedKey, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K: PackedKey, V> FusedIterator for Iter<'_, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ops::Bound;
+
+    #[test]
+    fn test_get() {
+        let m = (0..1000u32).map(|i| (i * 7 + 1_000_000, i)).collect::<PackedFlatMap<_, _>>();
+        assert_eq!(m.len(), 1000);
+        for i in 0..1000 {
+            let key = i * 7 + 1_000_000;
+            assert_eq!(m.get(&key), Some(&i));
+            assert_eq!(m.get(&(key + 1)), None);
+            assert_eq!(m.get_index(i as usize), Some((key, &i)));
+        }
+        assert_eq!(m.get(&0), None);
+        assert_eq!(m.rank(&u32::MAX), 1000);
+        assert!(m.key_bytes() < 1000 * 4 / 2);
+    }
+
+    #[test]
+    fn test_extremes() {
+        let keys = [i64::MIN, -1, 0, 1, i64::MAX];
+        let m = keys.iter().map(|&k| (k, ())).collect::<PackedFlatMap<_, _>>();
+        assert_eq!(m.keys().collect::<Vec<_>>(), keys);
+        assert!(keys.iter().all(|k| m.contains_key(k)));
+        assert_eq!(m.range(-1..=1).count(), 3);
+        assert_eq!(m.range((Bound::Excluded(i64::MAX), Bound::Unbounded)).count(), 0);
+        assert_eq!(m.clone().thaw().len(), 5);
+        assert!(PackedFlatMap::<u8, ()>::default().get(&0).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    fn test_range_reversed() {
+        let m = PackedFlatMap::from([(1u32, ()), (9, ())]);
+        let _ = m.range((Bound::Included(9), Bound::Excluded(1)));
+    }
+}
@@ -0,0 +1,81 @@
+//! Raw layout of frozen maps over plain-old-data keys and values.
+//!
+//! A 32-byte header (magic `FLPD`, byte order mark, version, key and value sizes and the entry
+//! count, all in native byte order), then all keys, then all values. Each array is padded to
+//! the alignment of its element type, so the whole thing can be reinterpreted in place.
+
+use crate::codec::invalid_data;
+use bytemuck::Pod;
+use std::{io::{self, Write}, ops::Range};
+
+const MAGIC: [u8; 4] = *b"FLPD";
+const BYTE_ORDER: u16 = 0xFEFF;
+const VERSION: u16 = 1;
+const HEADER_LEN: usize = 32;
+
+/// Byte ranges of the key and value arrays for `len` entries, `None` on overflow.
+fn layout<K, V>(len: usize) -> Option<(Range<usize>, Range<usize>)> {
+    let keys_start = HEADER_LEN.next_multiple_of(align_of::<K>());
+    let keys_end = keys_start.checked_add(len.checked_mul(size_of::<K>())?)?;
+    let values_start = keys_end.checked_next_multiple_of(align_of::<V>())?;
+    let values_end = values_start.checked_add(len.checked_mul(size_of::<V>())?)?;
+    Some((keys_start..keys_end, values_start..values_end))
+}
+
+pub(crate) fn write<K: Pod, V: Pod>(items: &[(K, V)], mut w: impl Write) -> io::Result<()> {
+    let (keys, values) = layout::<K, V>(items.len()).expect("map is too large");
+
+    let mut header = [0; HEADER_LEN];
+    header[..4].copy_from_slice(&MAGIC);
+    header[4..6].copy_from_slice(&BYTE_ORDER.to_ne_bytes());
+    header[6..8].copy_from_slice(&VERSION.to_ne_bytes());
+    header[8..12].copy_from_slice(&(size_of::<K>() as u32).to_ne_bytes());
+    header[12..16].copy_from_slice(&(size_of::<V>() as u32).to_ne_bytes());
+    header[16..24].copy_from_slice(&(items.len() as u64).to_ne_bytes());
+    w.write_all(&header)?;
+
+    w.write_all(&vec![0; keys.start - HEADER_LEN])?;
+    for (key, _) in items {
+        w.write_all(bytemuck::bytes_of(key))?;
+    }
+    w.write_all(&vec![0; values.start - keys.end])?;
+    for (_, value) in items {
+        w.write_all(bytemuck::bytes_of(value))?;
+    }
+    w.flush()
+}
+
+/// Validates the header of `bytes` and returns the byte ranges of the key and value arrays.
+pub(crate) fn parse<K: Pod, V: Pod>(bytes: &[u8]) -> io::Result<(Range<usize>, Range<usize>)> {
+    let header = bytes.get(..HEADER_LEN).ok_or(io::ErrorKind::UnexpectedEof)?;
+
+    if header[..4] != MAGIC {
+        return Err(invalid_data("not a flat_collections file"));
+    }
+    if u16::from_ne_bytes([header[4], header[5]]) != BYTE_ORDER {
+        return Err(invalid_data("file was written with a different byte order"));
+    }
+    let version = u16::from_ne_bytes([header[6], header[7]]);
+    if version != VERSION {
+        return Err(invalid_data(format!("unsupported format version {version}")));
+    }
+    let key_size = u32::from_ne_bytes(header[8..12].try_into().unwrap());
+    let value_size = u32::from_ne_bytes(header[12..16].try_into().unwrap());
+    if key_size as usize != size_of::<K>() || value_size as usize != size_of::<V>() {
+        return Err(invalid_data("key or value size does not match the file"));
+    }
+
+    let len = u64::from_ne_bytes(header[16..24].try_into().unwrap());
+    let (keys, values) = usize::try_from(len)
+        .ok()
+        .and_then(layout::<K, V>)
+        .ok_or_else(|| invalid_data("entry count is too large"))?;
+    if values.end > bytes.len() {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    let address = bytes.as_ptr() as usize;
+    if !(address + keys.start).is_multiple_of(align_of::<K>()) || !(address + values.start).is_multiple_of(align_of::<V>()) {
+        return Err(invalid_data("buffer is not sufficiently aligned"));
+    }
+    Ok((keys, values))
+}
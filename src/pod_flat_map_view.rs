@@ -0,0 +1,208 @@
+use crate::{pod, sorted, Comparable, FrozenFlatMap, NaturalOrder, NotSortedError};
+use bytemuck::Pod;
+use std::{fmt, io::{self, Write}, iter::Zip, ops::{Range, RangeBounds}, slice};
+
+/// Read-only map reinterpreting bytes written by [`FrozenFlatMap::write_pod_to`] in place.
+///
+/// Opening a view only validates the header, so e.g. a table embedded with `include_bytes!`
+/// is ready to use at startup without any parsing. Keys and values are stored in two
+/// separate arrays, like [`FrozenSoaFlatMap`](crate::FrozenSoaFlatMap).
+pub struct PodFlatMapView<'a, K: Ord + Pod, V: Pod> {
+    bytes: &'a [u8],
+    keys: &'a [K],
+    values: &'a [V],
+}
+
+impl<K: Ord + Pod, V: Pod> Clone for PodFlatMapView<'_, K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K: Ord + Pod, V: Pod> Copy for PodFlatMapView<'_, K, V> {}
+
+impl<K: Ord + Pod + fmt::Debug, V: Pod + fmt::Debug> fmt::Debug for PodFlatMapView<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K: Ord + Pod, V: Pod> IntoIterator for PodFlatMapView<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Zip<slice::Iter<'a, K>, slice::Iter<'a, V>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Ord + Pod, V: Pod> FrozenFlatMap<K, V> {
+    /// Writes the map in the layout read by [`PodFlatMapView::try_from_bytes`], in native byte order.
+    pub fn write_pod_to<W: Write>(&self, w: W) -> io::Result<()> {
+        pod::write(self.as_slice(), w)
+    }
+
+    /// Returns the map in the layout read by [`PodFlatMapView::try_from_bytes`].
+    pub fn to_pod_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        pod::write(self.as_slice(), &mut bytes).expect("writing to a Vec cannot fail");
+        bytes
+    }
+}
+
+impl<'a, K: Ord + Pod, V: Pod> PodFlatMapView<'a, K, V> {
+    /// Validates the header of `bytes`, but not the order of the keys, see
+    /// [`PodFlatMapView::check_invariants`].
+    ///
+    /// `bytes` must be aligned for both `K` and `V`. Data from `include_bytes!` is not
+    /// aligned by itself, so wrap it in a `#[repr(align(N))]` struct.
+    pub fn try_from_bytes(bytes: &'a [u8]) -> io::Result<Self> {
+        let (keys, values) = pod::parse::<K, V>(bytes)?;
+        Ok(Self::from_parts(bytes, keys, values))
+    }
+
+    pub(crate) fn from_parts(bytes: &'a [u8], keys: Range<usize>, values: Range<usize>) -> Self {
+        Self {
+            bytes,
+            keys: bytemuck::cast_slice(&bytes[keys]),
+            values: bytemuck::cast_slice(&bytes[values]),
+        }
+    }
+
+    /// Returns the bytes the view was created from.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Copies the entries into a [`FrozenFlatMap`] on the heap.
+    pub fn to_frozen(&self) -> FrozenFlatMap<K, V> {
+        FrozenFlatMap::from_sorted_unchecked(self.iter().map(|(k, v)| (*k, *v)).collect())
+    }
+
+    // lookup
+
+    pub fn contains_key<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> bool {
+        self.search(key).is_ok()
+    }
+
+    pub fn get<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<&'a V> {
+        self.search(key)
+            .ok()
+            .map(|i| &self.values[i])
+    }
+
+    pub fn get_key_value<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<(&'a K, &'a V)> {
+        self.search(key)
+            .ok()
+            .map(|i| (&self.keys[i], &self.values[i]))
+    }
+
+    // positional access
+
+    /// Returns the entry at position `index` in key order.
+    pub fn get_index(&self, index: usize) -> Option<(&'a K, &'a V)> {
+        Some((self.keys.get(index)?, &self.values[index]))
+    }
+
+    /// Returns the position of `key` in key order.
+    pub fn index_of<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<usize> {
+        self.search(key).ok()
+    }
+
+    /// Returns the number of keys strictly less than `key`.
+    pub fn rank<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> usize {
+        self.search(key).unwrap_or_else(|i| i)
+    }
+
+    // ranges
+
+    pub fn range<Q: ?Sized + Comparable<K>>(&self, range: impl RangeBounds<Q>) -> Zip<slice::Iter<'a, K>, slice::Iter<'a, V>> {
+        let range = sorted::key_range_indices(self.keys, range, &NaturalOrder);
+        self.keys[range.clone()].iter().zip(self.values[range].iter())
+    }
+
+    // misc
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Verifies that the keys are strictly increasing.
+    pub fn check_invariants(&self) -> Result<(), NotSortedError> {
+        match self.keys.windows(2).position(|w| w[0] >= w[1]) {
+            Some(index) => Err(NotSortedError { index: index + 1 }),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns all keys as a sorted slice.
+    pub fn keys_as_slice(&self) -> &'a [K] {
+        self.keys
+    }
+
+    /// Returns all values as a slice, in key order.
+    pub fn values_as_slice(&self) -> &'a [V] {
+        self.values
+    }
+
+    // iterators
+
+    pub fn iter(&self) -> Zip<slice::Iter<'a, K>, slice::Iter<'a, V>> {
+        self.keys.iter().zip(self.values.iter())
+    }
+
+    pub fn keys(&self) -> slice::Iter<'a, K> {
+        self.keys.iter()
+    }
+
+    pub fn values(&self) -> slice::Iter<'a, V> {
+        self.values.iter()
+    }
+
+    // internals
+
+    fn search<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Result<usize, usize> {
+        self.keys.binary_search_by(|probe| key.compare(probe).reverse())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[repr(C, align(16))]
+    struct Aligned<T: ?Sized>(T);
+
+    #[test]
+    fn test_round_trip() {
+        let m = FrozenFlatMap::from([(3i64, 30u32), (1, 10), (2, 20)]);
+        let bytes = m.to_pod_bytes();
+        let view = PodFlatMapView::<i64, u32>::try_from_bytes(&bytes).unwrap();
+        assert_eq!(view.as_bytes(), bytes.as_slice());
+        assert_eq!(view.keys_as_slice(), &[1, 2, 3]);
+        assert_eq!(view.get(&2), Some(&20));
+        assert_eq!(view.rank(&3), 2);
+        assert_eq!(view.check_invariants(), Ok(()));
+        assert_eq!(view.to_frozen(), m);
+    }
+
+    #[test]
+    fn test_alignment() {
+        static BYTES: &Aligned<[u8]> = &Aligned(*b"FLPD\xff\xfe\x01\x00\x08\x00\x00\x00\x01\x00\x00\x00\
+            \x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00");
+        if cfg!(target_endian = "little") {
+            let view = PodFlatMapView::<u64, u8>::try_from_bytes(&BYTES.0).unwrap();
+            assert!(view.is_empty());
+        }
+
+        let bytes = FrozenFlatMap::from([(1u64, 1u8)]).to_pod_bytes();
+        let mut buf = Aligned([0u8; 64]);
+        buf.0[1..=bytes.len()].copy_from_slice(&bytes);
+        let err = PodFlatMapView::<u64, u8>::try_from_bytes(&buf.0[1..=bytes.len()]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,127 @@
+//! Alternative lookup algorithms, plugged in as comparator adapters.
+//!
+//! `FlatMap<u64, V, Interpolation>` orders keys exactly like `FlatMap<u64, V>`, only
+//! exact lookups run a different search. To combine them with a custom order, wrap the
+//! comparator: `Branchless(Reversed(NaturalOrder))`.
+
+use crate::{Compare, NaturalOrder};
+use std::cmp::Ordering;
+
+/// Binary search without data-dependent branches in the loop.
+///
+/// Usually faster than the default search for maps that fit in cache.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Branchless<C = NaturalOrder>(pub C);
+
+impl<L: ?Sized, R: ?Sized, C: Compare<L, R>> Compare<L, R> for Branchless<C> {
+    fn compare(&self, l: &L, r: &R) -> Ordering {
+        self.0.compare(l, r)
+    }
+
+    fn search<V>(&self, items: &[(L, V)], key: &R) -> Result<usize, usize>
+    where
+        L: Sized,
+    {
+        if items.is_empty() {
+            return Err(0);
+        }
+        let mut base = 0;
+        let mut size = items.len();
+        while size > 1 {
+            let half = size / 2;
+            let mid = base + half;
+            // a select instead of a branch, the loop always runs log2(n) times
+            base = if self.0.compare(&items[mid].0, key) == Ordering::Greater { base } else { mid };
+            size -= half;
+        }
+        match self.0.compare(&items[base].0, key) {
+            Ordering::Equal => Ok(base),
+            Ordering::Less => Err(base + 1),
+            Ordering::Greater => Err(base),
+        }
+    }
+}
+
+/// Keys that can be mapped onto a number line for [`Interpolation`] search.
+///
+/// `position` must be monotone: `a < b` implies `a.position() <= b.position()`.
+pub trait Interpolate {
+    fn position(&self) -> u64;
+}
+
+macro_rules! impl_interpolate_unsigned {
+    ($($t:ty),*) => {$(
+        impl Interpolate for $t {
+            fn position(&self) -> u64 {
+                *self as u64
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_interpolate_signed {
+    ($($t:ty),*) => {$(
+        impl Interpolate for $t {
+            fn position(&self) -> u64 {
+                (*self as i64 as u64) ^ (1 << 63)
+            }
+        }
+    )*};
+}
+
+impl_interpolate_unsigned!(u8, u16, u32, u64, usize);
+impl_interpolate_signed!(i8, i16, i32, i64, isize);
+
+impl Interpolate for char {
+    fn position(&self) -> u64 {
+        *self as u64
+    }
+}
+
+/// Interpolation search: probes where the key is expected to be, assuming keys are spread
+/// evenly between the smallest and the largest one.
+///
+/// Takes O(log log n) probes on uniformly distributed keys. On skewed keys it falls back to
+/// binary search after O(log n) probes, and it stays correct (but slow) if the comparator
+/// does not order keys by ascending [`Interpolate::position`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interpolation<C = NaturalOrder>(pub C);
+
+impl<L, R, C> Compare<L, R> for Interpolation<C>
+where
+    L: ?Sized + Interpolate,
+    R: ?Sized + Interpolate,
+    C: Compare<L, R>,
+{
+    fn compare(&self, l: &L, r: &R) -> Ordering {
+        self.0.compare(l, r)
+    }
+
+    fn search<V>(&self, items: &[(L, V)], key: &R) -> Result<usize, usize>
+    where
+        L: Sized,
+    {
+        let target = key.position();
+        let (mut lo, mut hi) = (0, items.len());
+        let mut probes = usize::BITS - items.len().leading_zeros();
+        while hi - lo > 8 && probes > 0 {
+            probes -= 1;
+            let first = items[lo].0.position();
+            let last = items[hi - 1].0.position();
+            if last <= first {
+                break;
+            }
+            let offset = target.saturating_sub(first).min(last - first);
+            let mid = lo + (offset as u128 * (hi - 1 - lo) as u128 / (last - first) as u128) as usize;
+            match self.0.compare(&items[mid].0, key) {
+                Ordering::Less => lo = mid + 1,
+                Ordering::Greater => hi = mid,
+                Ordering::Equal => return Ok(mid),
+            }
+        }
+        match items[lo..hi].binary_search_by(|probe| self.0.compare(&probe.0, key)) {
+            Ok(i) => Ok(lo + i),
+            Err(i) => Err(lo + i),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Serde support, serializing maps as maps.
+//!
+//! Deserialization accepts entries in any order and keeps the last of duplicate keys. Keys
+//! and values borrow from the input wherever their types and the format allow it, e.g. a
+//! `FrozenFlatMap<&str, &str>` loaded from JSON without escapes points into the JSON text.
+
+use crate::{Compare, FlatMap, FrozenFlatMap, Storage};
+use serde::{de::{MapAccess, Visitor}, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, marker::PhantomData};
+
+impl<K: Serialize, V: Serialize, C: Compare<K>, S: Storage<(K, V)>> Serialize for FlatMap<K, V, C, S> {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+impl<K: Ord + Serialize, V: Serialize, C: Compare<K>> Serialize for FrozenFlatMap<K, V, C> {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+impl<'de, K, V, C> Deserialize<'de> for FlatMap<K, V, C>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+    C: Compare<K> + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let items = deserializer.deserialize_map(EntriesVisitor(PhantomData))?;
+        Ok(FlatMap::from_vec_with_comparator(items, C::default()))
+    }
+}
+
+impl<'de, K, V, C> Deserialize<'de> for FrozenFlatMap<K, V, C>
+where
+    K: Ord + Deserialize<'de>,
+    V: Deserialize<'de>,
+    C: Compare<K> + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        FlatMap::deserialize(deserializer).map(FrozenFlatMap::from)
+    }
+}
+
+/// Collects the entries of a serialized map in input order.
+struct EntriesVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K: Deserialize<'de>, V: Deserialize<'de>> Visitor<'de> for EntriesVisitor<K, V> {
+    type Value = Vec<(K, V)>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+        // don't trust the size hint with more than a modest preallocation
+        let mut items = Vec::with_capacity(access.size_hint().unwrap_or(0).min(4096));
+        while let Some(entry) = access.next_entry()? {
+            items.push(entry);
+        }
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let m = FlatMap::from([(3, "c".to_string()), (1, "a".to_string())]);
+        let json = serde_json::to_string(&m).unwrap();
+        assert_eq!(json, r#"{"1":"a","3":"c"}"#);
+        assert_eq!(serde_json::from_str::<FlatMap<i32, String>>(&json).unwrap(), m);
+        assert_eq!(serde_json::from_str::<FlatMap<i32, i32>>(r#"{"2":1,"1":2,"2":3}"#).unwrap(), FlatMap::from([(1, 2), (2, 3)]));
+    }
+
+    #[test]
+    fn test_borrowed() {
+        let json = String::from(r#"{"pear":"green","apple":"red"}"#);
+        let m = serde_json::from_str::<FrozenFlatMap<&str, &str>>(&json).unwrap();
+        assert_eq!(m.get("apple"), Some(&"red"));
+        assert!(json.as_bytes().as_ptr_range().contains(&m.get("pear").unwrap().as_ptr()));
+        assert_eq!(serde_json::to_string(&m).unwrap(), r#"{"apple":"red","pear":"green"}"#);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{flat_map::{Iter, Keys, Values}, sorted, Comparable, FlatMap, FlatMapView, FrozenFlatMap, NaturalOrder};
+use std::{fmt, hash::{Hash, Hasher}, ops::{Range, RangeBounds}, sync::Arc};
+
+/// Immutable map backed by an `Arc<[(K, V)]>` sorted by key.
+///
+/// Cloning is O(1), and [`SharedFlatMap::submap`] returns key ranges that share the same
+/// allocation, so one table can be handed out to many threads in pieces.
+pub struct SharedFlatMap<K: Ord, V> {
+    items: Arc<[(K, V)]>,
+    range: Range<usize>,
+}
+
+impl<K: Ord, V> Clone for SharedFlatMap<K, V> {
+    fn clone(&self) -> Self {
+        Self { items: Arc::clone(&self.items), range: self.range.clone() }
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for SharedFlatMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_view().fmt(f)
+    }
+}
+
+/// Maps are compared by their entries only.
+impl<K: Ord, V: PartialEq> PartialEq for SharedFlatMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<K: Ord, V: Eq> Eq for SharedFlatMap<K, V> {}
+
+impl<K: Ord + Hash, V: Hash> Hash for SharedFlatMap<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl<K: Ord, V> Default for SharedFlatMap<K, V> {
+    fn default() -> Self {
+        Self::from(FlatMap::new())
+    }
+}
+
+/// If there are duplicates, the last one is kept.
+impl<K: Ord, V> From<Vec<(K, V)>> for SharedFlatMap<K, V> {
+    fn from(items: Vec<(K, V)>) -> Self {
+        Self::from(FlatMap::from(items))
+    }
+}
+
+impl<K: Ord + Clone, V: Clone, const N: usize> From<[(K, V); N]> for SharedFlatMap<K, V> {
+    fn from(value: [(K, V); N]) -> Self {
+        Self::from(value.to_vec())
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SharedFlatMap<K, V> {
+    fn from_iter<I: IntoIterator<Item=(K, V)>>(iter: I) -> Self {
+        Self::from(FlatMap::from_iter(iter))
+    }
+}
+
+impl<K: Ord, V> From<FlatMap<K, V>> for SharedFlatMap<K, V> {
+    fn from(map: FlatMap<K, V>) -> Self {
+        let items = Arc::<[(K, V)]>::from(map.into_inner());
+        let range = 0..items.len();
+        Self { items, range }
+    }
+}
+
+impl<K: Ord, V> From<FrozenFlatMap<K, V>> for SharedFlatMap<K, V> {
+    fn from(map: FrozenFlatMap<K, V>) -> Self {
+        Self::from(map.thaw())
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a SharedFlatMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Ord, V> SharedFlatMap<K, V> {
+    /// Borrows the entries as a [`FlatMapView`].
+    pub fn as_view(&self) -> FlatMapView<'_, K, V> {
+        FlatMapView::from_sorted_unchecked(self.as_slice())
+    }
+
+    /// Returns the entries within `range` as a map sharing this map's allocation, in O(log n).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of `range` is greater than its end, like `FlatMap::range`.
+    pub fn submap<Q: ?Sized + Comparable<K>>(&self, range: impl RangeBounds<Q>) -> Self {
+        let sub = sorted::range_indices(self.as_slice(), range, &NaturalOrder);
+        assert!(sub.start <= sub.end, "range start is greater than range end in SharedFlatMap");
+        let start = self.range.start + sub.start;
+        Self { items: Arc::clone(&self.items), range: start..start + sub.len() }
+    }
+
+    // lookup
+
+    pub fn contains_key<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> bool {
+        self.as_view().contains_key(key)
+    }
+
+    pub fn get<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<&V> {
+        self.as_view().get(key)
+    }
+
+    pub fn get_key_value<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<(&K, &V)> {
+        self.as_view().get_key_value(key)
+    }
+
+    // positional access
+
+    /// Returns the entry at position `index` in key order.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.as_view().get_index(index)
+    }
+
+    /// Returns the position of `key` in key order.
+    pub fn index_of<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<usize> {
+        self.as_view().index_of(key)
+    }
+
+    // ranges
+
+    pub fn range<Q: ?Sized + Comparable<K>>(&self, range: impl RangeBounds<Q>) -> Iter<'_, K, V> {
+        self.as_view().range(range)
+    }
+
+    // misc
+
+    pub fn is_empty(&self) -> bool {
+        self.range.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.range.len()
+    }
+
+    /// Returns the entries as a slice sorted by key.
+    pub fn as_slice(&self) -> &[(K, V)] {
+        &self.items[self.range.clone()]
+    }
+
+    // iterators
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.as_view().iter()
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        self.as_view().keys()
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        self.as_view().values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ops::Bound;
+
+    #[test]
+    fn test_clone_shares_allocation() {
+        let m = SharedFlatMap::from([(1, 2), (3, 4)]);
+        let c = m.clone();
+        assert!(Arc::ptr_eq(&m.items, &c.items));
+        assert_eq!(m, c);
+        assert_eq!(c.get(&3), Some(&4));
+    }
+
+    #[test]
+    fn test_submap() {
+        let m = (0..10).map(|i| (i, i * 10)).collect::<SharedFlatMap<_, _>>();
+        let sub = m.submap(3..7);
+        assert!(Arc::ptr_eq(&m.items, &sub.items));
+        assert_eq!(sub.keys().copied().collect::<Vec<_>>(), vec![3, 4, 5, 6]);
+        assert_eq!(sub.get(&8), None);
+        assert_eq!(sub.index_of(&5), Some(2));
+
+        let subsub = sub.submap(5..);
+        assert_eq!(subsub.as_slice(), &[(5, 50), (6, 60)]);
+        assert!(sub.submap(8..).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    fn test_submap_reversed() {
+        let m = (0..10).map(|i| (i, i * 10)).collect::<SharedFlatMap<_, _>>();
+        let _ = m.submap((Bound::Included(7), Bound::Excluded(3)));
+    }
+
+    #[test]
+    fn test_send_to_threads() {
+        let m = (0..100).map(|i| (i, i)).collect::<SharedFlatMap<_, _>>();
+        let handles = (0..4)
+            .map(|t| {
+                let part = m.submap(t * 25..(t + 1) * 25);
+                std::thread::spawn(move || part.values().sum::<i32>())
+            })
+            .collect::<Vec<_>>();
+        let total = handles.into_iter().map(|h| h.join().unwrap()).sum::<i32>();
+        assert_eq!(total, (0..100).sum::<i32>());
+    }
+}
@@ -0,0 +1,221 @@
+//! Algorithms over slices of entries sorted by key, shared by all map types.
+
+use crate::Compare;
+use std::{cmp::Ordering, ops::{Bound, Range, RangeBounds}};
+
+pub(crate) fn search<K, V, Q, C>(items: &[(K, V)], key: &Q, cmp: &C) -> Result<usize, usize>
+where
+    Q: ?Sized,
+    C: Compare<K, Q>,
+{
+    cmp.search(items, key)
+}
+
+/// Position of the first key that is greater than or equal to `key`.
+pub(crate) fn lower_bound<K, V, Q, C>(items: &[(K, V)], key: &Q, cmp: &C) -> usize
+where
+    Q: ?Sized,
+    C: Compare<K, Q>,
+{
+    items.partition_point(|(k, _)| cmp.compare(k, key) == Ordering::Less)
+}
+
+/// Position of the first key that is greater than `key`.
+pub(crate) fn upper_bound<K, V, Q, C>(items: &[(K, V)], key: &Q, cmp: &C) -> usize
+where
+    Q: ?Sized,
+    C: Compare<K, Q>,
+{
+    items.partition_point(|(k, _)| cmp.compare(k, key) != Ordering::Greater)
+}
+
+/// Like [`lower_bound`], but probes exponentially growing offsets first, which makes it
+/// cheap when the answer is close to the start of `items`.
+pub(crate) fn gallop<K, V, Q, C>(items: &[(K, V)], key: &Q, cmp: &C) -> usize
+where
+    Q: ?Sized,
+    C: Compare<K, Q>,
+{
+    let mut bound = 1;
+    while bound < items.len() && cmp.compare(&items[bound - 1].0, key) == Ordering::Less {
+        bound *= 2;
+    }
+
+    let start = bound / 2;
+    let end = bound.min(items.len());
+    start + lower_bound(&items[start..end], key, cmp)
+}
+
+/// Like [`gallop`], but over a slice of bare keys.
+pub(crate) fn key_gallop<K, Q, C>(keys: &[K], key: &Q, cmp: &C) -> usize
+where
+    Q: ?Sized,
+    C: Compare<K, Q>,
+{
+    let lower_bound = |keys: &[K]| keys.partition_point(|k| cmp.compare(k, key) == Ordering::Less);
+
+    let mut bound = 1;
+    while bound < keys.len() && cmp.compare(&keys[bound - 1], key) == Ordering::Less {
+        bound *= 2;
+    }
+
+    let start = bound / 2;
+    let end = bound.min(keys.len());
+    start + lower_bound(&keys[start..end])
+}
+
+/// Like [`get_batch`], for `keys` that are already sorted.
+pub(crate) fn get_sorted_batch<'a, K, V, Q, C>(items: &'a [(K, V)], keys: &[Q], cmp: &C) -> Vec<Option<&'a V>>
+where
+    C: Compare<K, Q>,
+{
+    let mut start = 0;
+    keys.iter()
+        .map(|key| {
+            start += gallop(&items[start..], key, cmp);
+            items
+                .get(start)
+                .filter(|(k, _)| cmp.compare(k, key) == Ordering::Equal)
+                .map(|(_, v)| v)
+        })
+        .collect()
+}
+
+/// Answers lookups for `keys` with a single forward pass over `items`.
+pub(crate) fn get_batch<'a, K, V, Q, C>(items: &'a [(K, V)], keys: &[Q], cmp: &C) -> Vec<Option<&'a V>>
+where
+    C: Compare<K, Q> + Compare<Q>,
+{
+    let mut order = (0..keys.len()).collect::<Vec<_>>();
+    order.sort_unstable_by(|&a, &b| Compare::<Q>::compare(cmp, &keys[a], &keys[b]));
+
+    let mut result = vec![None; keys.len()];
+    let mut start = 0;
+    for i in order {
+        let key = &keys[i];
+        start += gallop(&items[start..], key, cmp);
+        result[i] = items
+            .get(start)
+            .filter(|(k, _)| cmp.compare(k, key) == Ordering::Equal)
+            .map(|(_, v)| v);
+    }
+    result
+}
+
+pub(crate) fn range_indices<K, V, Q, C>(items: &[(K, V)], range: impl RangeBounds<Q>, cmp: &C) -> Range<usize>
+where
+    Q: ?Sized,
+    C: Compare<K, Q>,
+{
+    bound_indices(range, items.len(), |key| lower_bound(items, key, cmp), |key| upper_bound(items, key, cmp))
+}
+
+/// Like [`range_indices`], but over a slice of bare keys.
+pub(crate) fn key_range_indices<K, Q, C>(keys: &[K], range: impl RangeBounds<Q>, cmp: &C) -> Range<usize>
+where
+    Q: ?Sized,
+    C: Compare<K, Q>,
+{
+    bound_indices(
+        range,
+        keys.len(),
+        |key| keys.partition_point(|k| cmp.compare(k, key) == Ordering::Less),
+        |key| keys.partition_point(|k| cmp.compare(k, key) != Ordering::Greater),
+    )
+}
+
+/// Maps the bounds of `range` to positions among `len` sorted keys, given functions that
+/// find the first key greater than or equal to, and greater than, a bound.
+///
+/// Reversed ranges yield `start > end`, so slicing with the result panics like `BTreeMap::range`.
+pub(crate) fn bound_indices<Q: ?Sized>(
+    range: impl RangeBounds<Q>,
+    len: usize,
+    lower_bound: impl Fn(&Q) -> usize,
+    upper_bound: impl Fn(&Q) -> usize,
+) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(key) => lower_bound(key),
+        Bound::Excluded(key) => upper_bound(key),
+        Bound::Unbounded => 0,
+    };
+
+    let end = match range.end_bound() {
+        Bound::Included(key) => upper_bound(key),
+        Bound::Excluded(key) => lower_bound(key),
+        Bound::Unbounded => len,
+    };
+
+    start..end
+}
+
+/// Like [`slice::partition_point`], over the indices `0..len` of a sequence that is only
+/// accessible by index.
+pub(crate) fn index_partition_point(len: usize, mut pred: impl FnMut(usize) -> bool) -> usize {
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if pred(mid) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+/// Sorts the entries by key, keeping only the last of several entries with the same key.
+pub(crate) fn sort_dedup<K, V, C: Compare<K>>(items: &mut Vec<(K, V)>, cmp: &C) {
+    items.reverse();
+    items.sort_by(|a, b| cmp.compare(&a.0, &b.0));
+    items.dedup_by(|a, b| cmp.compare(&a.0, &b.0) == Ordering::Equal);
+}
+
+/// Returns the index of the first entry that is not greater than its predecessor.
+pub(crate) fn first_unsorted<K, V, C: Compare<K>>(items: &[(K, V)], cmp: &C) -> Option<usize> {
+    items
+        .windows(2)
+        .position(|w| cmp.compare(&w[0].0, &w[1].0) != Ordering::Less)
+        .map(|i| i + 1)
+}
+
+/// Merges two sorted and deduplicated vectors in a single pass.
+///
+/// On equal keys the key from `left` is kept and the value is produced by `resolve`.
+pub(crate) fn merge<K, V, C: Compare<K>>(
+    mut left: Vec<(K, V)>,
+    mut right: Vec<(K, V)>,
+    cmp: &C,
+    mut resolve: impl FnMut(&K, V, V) -> V,
+) -> Vec<(K, V)> {
+    match (left.last(), right.first()) {
+        (_, None) => return left,
+        (None, _) => return right,
+        (Some((l, _)), Some((r, _))) if cmp.compare(l, r) == Ordering::Less => {
+            left.append(&mut right);
+            return left;
+        }
+        _ => {}
+    }
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+
+    while let (Some((l, _)), Some((r, _))) = (left.peek(), right.peek()) {
+        match cmp.compare(l, r) {
+            Ordering::Less => merged.extend(left.next()),
+            Ordering::Greater => merged.extend(right.next()),
+            Ordering::Equal => {
+                let (k, lv) = left.next().unwrap();
+                let (_, rv) = right.next().unwrap();
+                let v = resolve(&k, lv, rv);
+                merged.push((k, v));
+            }
+        }
+    }
+
+    merged.extend(left);
+    merged.extend(right);
+    merged
+}
@@ -0,0 +1,192 @@
+//! Containers a [`FlatMap`](crate::FlatMap) can keep its sorted entries in.
+//!
+//! `Vec` is the default and the only storage that supports the whole API. Boxed slices
+//! only support lookups, `SmallVec` and `ArrayVec` (behind the `smallvec` and `arrayvec`
+//! features) support lookups, insertion and removal without a heap allocation.
+
+/// Read access to the entries of a map.
+pub trait Storage<T> {
+    fn as_slice(&self) -> &[T];
+
+    fn as_mut_slice(&mut self) -> &mut [T];
+}
+
+/// Storage that can grow and shrink.
+///
+/// Fixed-capacity storage panics when an insertion exceeds its capacity.
+pub trait StorageMut<T>: Storage<T> {
+    fn with_capacity(capacity: usize) -> Self;
+
+    fn capacity(&self) -> usize;
+
+    fn reserve(&mut self, additional: usize);
+
+    fn insert(&mut self, index: usize, item: T);
+
+    fn remove(&mut self, index: usize) -> T;
+
+    fn push(&mut self, item: T);
+
+    fn pop(&mut self) -> Option<T>;
+
+    fn clear(&mut self);
+
+    fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, f: F);
+}
+
+impl<T> Storage<T> for Vec<T> {
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T> StorageMut<T> for Vec<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        Vec::with_capacity(capacity)
+    }
+
+    fn capacity(&self) -> usize {
+        Vec::capacity(self)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional);
+    }
+
+    fn insert(&mut self, index: usize, item: T) {
+        Vec::insert(self, index, item);
+    }
+
+    fn remove(&mut self, index: usize) -> T {
+        Vec::remove(self, index)
+    }
+
+    fn push(&mut self, item: T) {
+        Vec::push(self, item);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        Vec::pop(self)
+    }
+
+    fn clear(&mut self) {
+        Vec::clear(self);
+    }
+
+    fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, f: F) {
+        Vec::retain_mut(self, f);
+    }
+}
+
+impl<T> Storage<T> for Box<[T]> {
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> Storage<A::Item> for smallvec::SmallVec<A> {
+    fn as_slice(&self) -> &[A::Item] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [A::Item] {
+        self
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> StorageMut<A::Item> for smallvec::SmallVec<A> {
+    fn with_capacity(capacity: usize) -> Self {
+        smallvec::SmallVec::with_capacity(capacity)
+    }
+
+    fn capacity(&self) -> usize {
+        smallvec::SmallVec::capacity(self)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        smallvec::SmallVec::reserve(self, additional);
+    }
+
+    fn insert(&mut self, index: usize, item: A::Item) {
+        smallvec::SmallVec::insert(self, index, item);
+    }
+
+    fn remove(&mut self, index: usize) -> A::Item {
+        smallvec::SmallVec::remove(self, index)
+    }
+
+    fn push(&mut self, item: A::Item) {
+        smallvec::SmallVec::push(self, item);
+    }
+
+    fn pop(&mut self) -> Option<A::Item> {
+        smallvec::SmallVec::pop(self)
+    }
+
+    fn clear(&mut self) {
+        smallvec::SmallVec::clear(self);
+    }
+
+    fn retain_mut<F: FnMut(&mut A::Item) -> bool>(&mut self, f: F) {
+        smallvec::SmallVec::retain_mut(self, f);
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<T, const N: usize> Storage<T> for arrayvec::ArrayVec<T, N> {
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+/// Panics when inserting into a full array.
+#[cfg(feature = "arrayvec")]
+impl<T, const N: usize> StorageMut<T> for arrayvec::ArrayVec<T, N> {
+    fn with_capacity(_: usize) -> Self {
+        arrayvec::ArrayVec::new()
+    }
+
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn reserve(&mut self, _: usize) {}
+
+    fn insert(&mut self, index: usize, item: T) {
+        arrayvec::ArrayVec::insert(self, index, item);
+    }
+
+    fn remove(&mut self, index: usize) -> T {
+        arrayvec::ArrayVec::remove(self, index)
+    }
+
+    fn push(&mut self, item: T) {
+        arrayvec::ArrayVec::push(self, item);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        arrayvec::ArrayVec::pop(self)
+    }
+
+    fn clear(&mut self) {
+        arrayvec::ArrayVec::clear(self);
+    }
+
+    fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, f: F) {
+        arrayvec::ArrayVec::retain(self, f);
+    }
+}
@@ -0,0 +1,30 @@
+#![cfg(feature = "macros")]
+
+use flat_collections::{frozen_flat_map, FlatMapView};
+
+static COLORS: FlatMapView<&str, u32> = frozen_flat_map! {
+    "red" => 0xff0000,
+    "green" => 0x00ff00,
+    "blue" => 0x0000ff,
+    "red" => 0xee0000,
+};
+
+#[test]
+fn test_static_map() {
+    assert_eq!(COLORS.keys().collect::<Vec<_>>(), vec![&"blue", &"green", &"red"]);
+    assert_eq!(COLORS.get("red"), Some(&0xee0000));
+    assert_eq!(COLORS.range("c".."h").count(), 1);
+}
+
+#[test]
+fn test_integer_keys() {
+    const SQUARES: FlatMapView<i64, i64> = frozen_flat_map! { 3 => 9, -2 => 4, 0 => 0, 10 => 100 };
+    assert_eq!(SQUARES.keys().copied().collect::<Vec<_>>(), vec![-2, 0, 3, 10]);
+    assert_eq!(SQUARES.get(&-2), Some(&4));
+}
+
+#[test]
+fn test_empty() {
+    let empty: FlatMapView<char, ()> = frozen_flat_map! {};
+    assert!(empty.is_empty());
+}